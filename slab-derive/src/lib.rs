@@ -0,0 +1,399 @@
+#![warn(missing_debug_implementations, rust_2018_idioms, unreachable_pub)]
+
+//! Derive macros for the [`slab`](https://docs.rs/slab) crate.
+//!
+//! Use them through the `derive` feature of `slab` rather than depending on
+//! this crate directly.
+
+use std::collections::HashMap;
+
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::{parse_macro_input, parse_quote, Data, DeriveInput, Error, Fields, Index, LitStr, Path};
+
+/// Generate a struct-of-arrays slab for a struct with named fields.
+///
+/// For a struct `Foo`, this generates `FooSlab`, `FooRef`, `FooMut` and
+/// `FooColumnsMut`. See the `slab::soa` module for the generated API.
+///
+/// The generated code refers to the `slab` crate as `::slab`. If it is
+/// available under another path, for example because the dependency was
+/// renamed, set that path with `#[slab(crate = "path")]` on the struct.
+///
+/// Columns store their values in a `slab::list::VecStorage`. Set another
+/// `ListStorage` for a field with `#[slab(storage = "path")]` on the field.
+#[proc_macro_derive(SlabSoA, attributes(slab))]
+pub fn derive_slab_soa(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: DeriveInput) -> Result<TokenStream, Error> {
+    if !input.generics.params.is_empty() {
+        return Err(Error::new_spanned(
+            &input.generics,
+            "SlabSoA does not support generic structs",
+        ));
+    }
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(Error::new(
+                    Span::call_site(),
+                    "SlabSoA requires a struct with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(Error::new(
+                Span::call_site(),
+                "SlabSoA can only be derived for structs",
+            ))
+        }
+    };
+
+    if fields.is_empty() {
+        return Err(Error::new(
+            Span::call_site(),
+            "SlabSoA requires at least one field",
+        ));
+    }
+
+    let mut krate: Path = parse_quote!(::slab);
+    for attr in &input.attrs {
+        if attr.path().is_ident("slab") {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("crate") {
+                    krate = meta.value()?.parse::<LitStr>()?.parse()?;
+                    Ok(())
+                } else {
+                    Err(meta.error("unsupported slab attribute"))
+                }
+            })?;
+        }
+    }
+
+    // Like the methods of `Slab`, `remove` reports the location of its
+    // caller unless the toolchain doesn't support it. The proc macro is
+    // built by the same toolchain as the generated code.
+    let track_caller = if cfg!(slab_no_track_caller) {
+        quote!()
+    } else {
+        quote!(#[track_caller])
+    };
+
+    let vis = &input.vis;
+    let name = &input.ident;
+    let slab = format_ident!("{}Slab", name);
+    let item_ref = format_ident!("{}Ref", name);
+    let item_mut = format_ident!("{}Mut", name);
+    let columns_mut = format_ident!("{}ColumnsMut", name);
+
+    let names: Vec<_> = fields.iter().map(|f| f.ident.as_ref().unwrap()).collect();
+    let types: Vec<_> = fields.iter().map(|f| &f.ty).collect();
+    let storages = fields
+        .iter()
+        .map(|field| {
+            let mut storage: Path = parse_quote!(#krate::list::VecStorage);
+            for attr in &field.attrs {
+                if attr.path().is_ident("slab") {
+                    attr.parse_nested_meta(|meta| {
+                        if meta.path.is_ident("storage") {
+                            storage = meta.value()?.parse::<LitStr>()?.parse()?;
+                            Ok(())
+                        } else {
+                            Err(meta.error("unsupported slab attribute"))
+                        }
+                    })?;
+                }
+            }
+            Ok(storage)
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+    let indices: Vec<_> = (0..fields.len()).map(Index::from).collect();
+    let iter_names: Vec<_> = names.iter().map(|n| format_ident!("iter_{}", n)).collect();
+    let iter_mut_names: Vec<_> = names
+        .iter()
+        .map(|n| format_ident!("iter_{}_mut", n))
+        .collect();
+
+    // Fields such as `pos` and `pos_mut` would both get an `iter_pos_mut`
+    let iter_fields: HashMap<_, _> = iter_names.iter().zip(&names).collect();
+    for (field, method) in names.iter().zip(&iter_mut_names) {
+        if let Some(other) = iter_fields.get(method) {
+            return Err(Error::new_spanned(
+                other,
+                format!(
+                    "SlabSoA generates `{}` for both `{}` and `{}`, rename one of the fields",
+                    method, field, other
+                ),
+            ));
+        }
+    }
+
+    let slab_doc = format!("Struct-of-arrays storage for [`{}`] values.", name);
+    let ref_doc = format!(
+        "References to the fields of a [`{}`] in a [`{}`].",
+        name, slab
+    );
+    let mut_doc = format!(
+        "Mutable references to the fields of a [`{}`] in a [`{}`].",
+        name, slab
+    );
+    let columns_mut_doc = format!("Mutable iterators over every column of a [`{}`].", slab);
+    let iter_docs: Vec<_> = names
+        .iter()
+        .map(|n| format!("Return an iterator over the `{}` column.", n))
+        .collect();
+    let iter_mut_docs: Vec<_> = names
+        .iter()
+        .map(|n| format!("Return a mutable iterator over the `{}` column.", n))
+        .collect();
+
+    Ok(quote! {
+        #[doc = #slab_doc]
+        #vis struct #slab {
+            storage: #krate::soa::Storage<(#(#krate::soa::Column<#types, #storages>,)*)>,
+        }
+
+        #[doc = #ref_doc]
+        #[allow(missing_debug_implementations)]
+        #vis struct #item_ref<'a> {
+            #(
+                #[allow(missing_docs)]
+                pub #names: &'a #types,
+            )*
+        }
+
+        #[doc = #mut_doc]
+        #[allow(missing_debug_implementations)]
+        #vis struct #item_mut<'a> {
+            #(
+                #[allow(missing_docs)]
+                pub #names: &'a mut #types,
+            )*
+        }
+
+        #[doc = #columns_mut_doc]
+        #[allow(missing_debug_implementations)]
+        #vis struct #columns_mut<'a> {
+            #(
+                #[allow(missing_docs)]
+                pub #names: #krate::soa::ColumnIterMut<'a, #types>,
+            )*
+        }
+
+        #[allow(dead_code)]
+        impl #slab {
+            /// Construct a new, empty slab.
+            pub fn new() -> Self {
+                Self::with_capacity(0)
+            }
+
+            /// Construct a new, empty slab with the specified capacity.
+            pub fn with_capacity(capacity: usize) -> Self {
+                Self {
+                    storage: #krate::soa::Storage::new(
+                        capacity,
+                        (#(#krate::soa::Column::<#types, #storages>::with_capacity(capacity),)*),
+                    ),
+                }
+            }
+
+            /// Return the number of values the slab can store without
+            /// reallocating.
+            pub fn capacity(&self) -> usize {
+                self.storage.keys().capacity()
+            }
+
+            /// Reserve capacity for at least `additional` more values.
+            pub fn reserve(&mut self, additional: usize) {
+                // Safety: no keys are occupied or vacated
+                let (keys, columns) = unsafe { self.storage.parts_mut() };
+                keys.reserve(additional);
+                #(columns.#indices.reserve(additional);)*
+            }
+
+            /// Return the number of stored values.
+            pub fn len(&self) -> usize {
+                self.storage.keys().len()
+            }
+
+            /// Return `true` if there are no values stored in the slab.
+            pub fn is_empty(&self) -> bool {
+                self.storage.keys().is_empty()
+            }
+
+            /// Return `true` if a value is associated with the given key.
+            pub fn contains(&self, key: usize) -> bool {
+                self.storage.keys().contains(key)
+            }
+
+            /// Insert a value in the slab, returning the key assigned to it.
+            pub fn insert(&mut self, value: #name) -> usize {
+                // Safety: the key is only occupied after every column has
+                // been written
+                let (keys, columns) = unsafe { self.storage.parts_mut() };
+                let key = keys.vacant_key();
+                #(columns.#indices.write(key, value.#names);)*
+                keys.insert(());
+                key
+            }
+
+            /// Return references to the fields of the value associated with
+            /// the given key.
+            pub fn get(&self, key: usize) -> ::core::option::Option<#item_ref<'_>> {
+                if !self.contains(key) {
+                    return ::core::option::Option::None;
+                }
+                let columns = self.storage.columns();
+                // Safety: the key is occupied
+                unsafe {
+                    ::core::option::Option::Some(#item_ref {
+                        #(#names: columns.#indices.get(key),)*
+                    })
+                }
+            }
+
+            /// Return mutable references to the fields of the value
+            /// associated with the given key.
+            pub fn get_mut(&mut self, key: usize) -> ::core::option::Option<#item_mut<'_>> {
+                // Safety: no keys are occupied or vacated
+                let (keys, columns) = unsafe { self.storage.parts_mut() };
+                if !keys.contains(key) {
+                    return ::core::option::Option::None;
+                }
+                // Safety: the key is occupied
+                unsafe {
+                    ::core::option::Option::Some(#item_mut {
+                        #(#names: columns.#indices.get_mut(key),)*
+                    })
+                }
+            }
+
+            /// Remove and return the value associated with the given key.
+            ///
+            /// If the given key is not associated with a value, then `None`
+            /// is returned.
+            pub fn try_remove(&mut self, key: usize) -> ::core::option::Option<#name> {
+                // Safety: the key is vacated before its values are taken
+                let (keys, columns) = unsafe { self.storage.parts_mut() };
+                keys.try_remove(key)?;
+                // Safety: the key was occupied
+                unsafe {
+                    ::core::option::Option::Some(#name {
+                        #(#names: columns.#indices.take(key),)*
+                    })
+                }
+            }
+
+            /// Remove and return the value associated with the given key.
+            ///
+            /// # Panics
+            ///
+            /// Panics if `key` is not associated with a value.
+            #track_caller
+            pub fn remove(&mut self, key: usize) -> #name {
+                self.try_remove(key).expect("invalid key")
+            }
+
+            /// Remove all values from the slab.
+            ///
+            /// This has no effect on the allocated capacity.
+            pub fn clear(&mut self) {
+                // Vacates every key once the values are dropped, or when a
+                // destructor panics, which leaks the values that are left
+                struct ClearGuard<'a>(&'a mut #krate::Slab<()>);
+                impl ::core::ops::Drop for ClearGuard<'_> {
+                    fn drop(&mut self) {
+                        self.0.clear();
+                    }
+                }
+
+                // Safety: the keys are all vacated before they can be used
+                // again, and values are only taken from occupied keys
+                let (keys, columns) = unsafe { self.storage.parts_mut() };
+                let keys = ClearGuard(keys);
+                for (key, ()) in keys.0.iter() {
+                    // Safety: the key is occupied, and vacated by the guard
+                    // before its slots are written again
+                    let _ = unsafe {
+                        #name {
+                            #(#names: columns.#indices.take(key),)*
+                        }
+                    };
+                }
+            }
+
+            /// Return an iterator over the keys and fields of the stored
+            /// values.
+            pub fn iter(&self) -> impl ::core::iter::Iterator<Item = (usize, #item_ref<'_>)> + '_ {
+                let columns = self.storage.columns();
+                self.storage.keys().iter().map(move |(key, ())| {
+                    // Safety: the key is occupied
+                    let item = unsafe {
+                        #item_ref {
+                            #(#names: columns.#indices.get(key),)*
+                        }
+                    };
+                    (key, item)
+                })
+            }
+
+            #(
+                #[doc = #iter_docs]
+                pub fn #iter_names(&self) -> #krate::soa::ColumnIter<'_, #types, #storages> {
+                    let keys = self.storage.keys().iter();
+                    // Safety: the keys are occupied in every column
+                    unsafe { #krate::soa::ColumnIter::new(keys, &self.storage.columns().#indices) }
+                }
+
+                #[doc = #iter_mut_docs]
+                pub fn #iter_mut_names(&mut self) -> #krate::soa::ColumnIterMut<'_, #types> {
+                    // Safety: no keys are occupied or vacated
+                    let (keys, columns) = unsafe { self.storage.parts_mut() };
+                    // Safety: the keys are occupied in every column
+                    unsafe { #krate::soa::ColumnIterMut::new((&*keys).iter(), &mut columns.#indices) }
+                }
+            )*
+
+            /// Return mutable iterators over every column, to update several
+            /// columns in one pass.
+            pub fn columns_mut(&mut self) -> #columns_mut<'_> {
+                // Safety: no keys are occupied or vacated
+                let (keys, columns) = unsafe { self.storage.parts_mut() };
+                let keys = &*keys;
+                // Safety: the keys are occupied in every column
+                unsafe {
+                    #columns_mut {
+                        #(#names: #krate::soa::ColumnIterMut::new(keys.iter(), &mut columns.#indices),)*
+                    }
+                }
+            }
+        }
+
+        impl ::core::default::Default for #slab {
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+
+        impl ::core::ops::Drop for #slab {
+            fn drop(&mut self) {
+                self.clear();
+            }
+        }
+
+        impl ::core::fmt::Debug for #slab {
+            fn fmt(&self, fmt: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                fmt.debug_struct(::core::stringify!(#slab))
+                    .field("len", &self.len())
+                    .field("cap", &self.capacity())
+                    .finish()
+            }
+        }
+    })
+}
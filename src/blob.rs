@@ -0,0 +1,368 @@
+//! A slab for byte blobs of different lengths.
+//!
+//! [`BlobSlab`] packs blobs into pages of fixed-size slots. Every power of two
+//! from 16 to 4096 bytes is a size class with its own pages, and a blob is
+//! stored in a slot of the smallest class it fits in. Each size class tracks
+//! its slots with a [`Slab`], so freed slots are reused through its vacant
+//! list just like keys are. Blobs larger than the biggest size class get an
+//! allocation of their own.
+//!
+//! The key of a blob is stable until the blob is removed, even when
+//! [`BlobSlab::compact`] moves blobs into lower slots to release pages.
+//!
+//! # Examples
+//!
+//! ```
+//! use slab::blob::BlobSlab;
+//!
+//! let mut blobs = BlobSlab::new();
+//! let hello = blobs.insert(b"hello");
+//! let world = blobs.insert(b"world!");
+//! assert_eq!(blobs.get(hello), Some(&b"hello"[..]));
+//!
+//! blobs.remove(hello);
+//! blobs.compact();
+//! assert_eq!(&blobs[world], b"world!");
+//! ```
+
+use alloc::boxed::Box;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::iter::FusedIterator;
+use core::{fmt, ops};
+
+use crate::Slab;
+
+// Slot size of the smallest class is `1 << MIN_SHIFT` bytes
+const MIN_SHIFT: u32 = 4;
+const CLASSES: usize = 9;
+const PAGE_SIZE: usize = 16 * 1024;
+
+/// Storage for variable-length byte blobs with stable keys.
+///
+/// See the [module documentation] for more details.
+///
+/// [module documentation]: index.html
+pub struct BlobSlab {
+    locations: Slab<Location>,
+    classes: [SizeClass; CLASSES],
+
+    // Blobs larger than the biggest class, with their keys
+    large: Slab<(usize, Box<[u8]>)>,
+}
+
+#[derive(Clone, Copy)]
+struct Location {
+    len: usize,
+    class: usize,
+
+    // Slot in the class, or key in `large` if `class` is `CLASSES`
+    slot: usize,
+}
+
+struct SizeClass {
+    // Key of the blob in every occupied slot
+    slots: Slab<usize>,
+    pages: Vec<Box<[u8]>>,
+}
+
+/// An iterator over the keys and blobs of a [`BlobSlab`] in key order.
+pub struct Iter<'a> {
+    locations: crate::Iter<'a, Location>,
+    slab: &'a BlobSlab,
+}
+
+fn slot_size(class: usize) -> usize {
+    1 << (MIN_SHIFT as usize + class)
+}
+
+// Return the smallest class with slots of at least `len` bytes, or
+// `CLASSES` if there is none.
+fn class_of(len: usize) -> usize {
+    let shift = len.next_power_of_two().trailing_zeros();
+    let class = shift.saturating_sub(MIN_SHIFT) as usize;
+    class.min(CLASSES)
+}
+
+impl SizeClass {
+    fn new() -> Self {
+        Self {
+            slots: Slab::new(),
+            pages: Vec::new(),
+        }
+    }
+
+    fn slots_per_page(class: usize) -> usize {
+        PAGE_SIZE / slot_size(class)
+    }
+
+    // Return the page and byte range of a slot
+    fn position(class: usize, slot: usize, len: usize) -> (usize, ops::Range<usize>) {
+        let per_page = Self::slots_per_page(class);
+        let start = (slot % per_page) * slot_size(class);
+        (slot / per_page, start..start + len)
+    }
+
+    fn bytes(&self, class: usize, slot: usize, len: usize) -> &[u8] {
+        let (page, range) = Self::position(class, slot, len);
+        &self.pages[page][range]
+    }
+
+    fn bytes_mut(&mut self, class: usize, slot: usize, len: usize) -> &mut [u8] {
+        let (page, range) = Self::position(class, slot, len);
+        &mut self.pages[page][range]
+    }
+
+    fn insert(&mut self, class: usize, key: usize, blob: &[u8]) -> usize {
+        let slot = self.slots.insert(key);
+        let (page, _) = Self::position(class, slot, 0);
+        while self.pages.len() <= page {
+            self.pages.push(vec![0; PAGE_SIZE].into_boxed_slice());
+        }
+        self.bytes_mut(class, slot, blob.len())
+            .copy_from_slice(blob);
+        slot
+    }
+
+    // Move the blobs in the highest slots into vacant lower ones and release
+    // the pages that are no longer used.
+    fn compact(&mut self, class: usize, locations: &mut Slab<Location>) {
+        let pages = &mut self.pages;
+        self.slots.compact(|&mut key, from, to| {
+            let location = &mut locations[key];
+            let (from_page, from_range) = Self::position(class, from, location.len);
+            let (to_page, to_range) = Self::position(class, to, location.len);
+            if from_page == to_page {
+                pages[from_page].copy_within(from_range, to_range.start);
+            } else {
+                // Blobs only move to lower slots
+                let (low, high) = pages.split_at_mut(from_page);
+                low[to_page][to_range].copy_from_slice(&high[0][from_range]);
+            }
+            location.slot = to;
+            true
+        });
+        let per_page = Self::slots_per_page(class);
+        pages.truncate((self.slots.len() + per_page - 1) / per_page);
+        pages.shrink_to_fit();
+    }
+}
+
+impl BlobSlab {
+    /// Construct a new, empty `BlobSlab`.
+    ///
+    /// No pages are allocated until blobs are inserted.
+    pub fn new() -> Self {
+        Self {
+            locations: Slab::new(),
+            classes: [(); CLASSES].map(|()| SizeClass::new()),
+            large: Slab::new(),
+        }
+    }
+
+    /// Return the number of stored blobs.
+    pub fn len(&self) -> usize {
+        self.locations.len()
+    }
+
+    /// Return `true` if there are no blobs stored in the slab.
+    pub fn is_empty(&self) -> bool {
+        self.locations.is_empty()
+    }
+
+    /// Return the number of bytes allocated for pages and large blobs.
+    pub fn allocated_bytes(&self) -> usize {
+        let pages: usize = self.classes.iter().map(|class| class.pages.len()).sum();
+        let large: usize = self.large.iter().map(|(_, (_, blob))| blob.len()).sum();
+        pages * PAGE_SIZE + large
+    }
+
+    /// Remove all blobs and release all pages.
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Return `true` if a blob is associated with the given key.
+    pub fn contains(&self, key: usize) -> bool {
+        self.locations.contains(key)
+    }
+
+    /// Return the blob associated with the given key.
+    ///
+    /// If the given key is not associated with a blob, then `None` is
+    /// returned.
+    pub fn get(&self, key: usize) -> Option<&[u8]> {
+        let location = *self.locations.get(key)?;
+        Some(self.bytes(location))
+    }
+
+    /// Return the blob associated with the given key mutably.
+    ///
+    /// The length of the blob cannot be changed. If the given key is not
+    /// associated with a blob, then `None` is returned.
+    pub fn get_mut(&mut self, key: usize) -> Option<&mut [u8]> {
+        let location = *self.locations.get(key)?;
+        Some(match self.classes.get_mut(location.class) {
+            Some(class) => class.bytes_mut(location.class, location.slot, location.len),
+            None => &mut self.large[location.slot].1,
+        })
+    }
+
+    fn bytes(&self, location: Location) -> &[u8] {
+        match self.classes.get(location.class) {
+            Some(class) => class.bytes(location.class, location.slot, location.len),
+            None => &self.large[location.slot].1,
+        }
+    }
+
+    /// Copy a blob into the slab, returning the key assigned to it.
+    pub fn insert(&mut self, blob: &[u8]) -> usize {
+        let key = self.locations.vacant_key();
+        let class = class_of(blob.len());
+        let slot = match self.classes.get_mut(class) {
+            Some(size_class) => size_class.insert(class, key, blob),
+            None => self.large.insert((key, blob.into())),
+        };
+        self.locations.insert(Location {
+            len: blob.len(),
+            class,
+            slot,
+        })
+    }
+
+    /// Remove the blob associated with the given key.
+    ///
+    /// Its slot is reused by later blobs of the same size class. Returns
+    /// `false` if the given key is not associated with a blob.
+    pub fn try_remove(&mut self, key: usize) -> bool {
+        let location = match self.locations.try_remove(key) {
+            Some(location) => location,
+            None => return false,
+        };
+        match self.classes.get_mut(location.class) {
+            Some(class) => {
+                class.slots.remove(location.slot);
+            }
+            None => {
+                self.large.remove(location.slot);
+            }
+        }
+        true
+    }
+
+    /// Remove the blob associated with the given key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `key` is not associated with a blob.
+    #[cfg_attr(not(slab_no_track_caller), track_caller)]
+    pub fn remove(&mut self, key: usize) {
+        assert!(self.try_remove(key), "invalid key");
+    }
+
+    /// Move blobs into the lowest free slots of their size class and release
+    /// the pages that become empty.
+    ///
+    /// Keys are not changed.
+    pub fn compact(&mut self) {
+        for (class, size_class) in self.classes.iter_mut().enumerate() {
+            size_class.compact(class, &mut self.locations);
+        }
+        let locations = &mut self.locations;
+        self.large.compact(|&mut (key, _), _, to| {
+            locations[key].slot = to;
+            true
+        });
+    }
+
+    /// Return an iterator over the keys and blobs in key order.
+    pub fn iter(&self) -> Iter<'_> {
+        Iter {
+            locations: self.locations.iter(),
+            slab: self,
+        }
+    }
+}
+
+impl Default for BlobSlab {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ops::Index<usize> for BlobSlab {
+    type Output = [u8];
+
+    #[cfg_attr(not(slab_no_track_caller), track_caller)]
+    fn index(&self, key: usize) -> &[u8] {
+        self.get(key).expect("invalid key")
+    }
+}
+
+impl ops::IndexMut<usize> for BlobSlab {
+    #[cfg_attr(not(slab_no_track_caller), track_caller)]
+    fn index_mut(&mut self, key: usize) -> &mut [u8] {
+        self.get_mut(key).expect("invalid key")
+    }
+}
+
+impl<'a> IntoIterator for &'a BlobSlab {
+    type Item = (usize, &'a [u8]);
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Iter<'a> {
+        self.iter()
+    }
+}
+
+impl fmt::Debug for BlobSlab {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("BlobSlab")
+            .field("len", &self.len())
+            .field("allocated_bytes", &self.allocated_bytes())
+            .finish()
+    }
+}
+
+// ===== Iter =====
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = (usize, &'a [u8]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (key, &location) = self.locations.next()?;
+        Some((key, self.slab.bytes(location)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.locations.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for Iter<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let (key, &location) = self.locations.next_back()?;
+        Some((key, self.slab.bytes(location)))
+    }
+}
+
+impl ExactSizeIterator for Iter<'_> {}
+
+impl FusedIterator for Iter<'_> {}
+
+impl Clone for Iter<'_> {
+    fn clone(&self) -> Self {
+        Self {
+            locations: self.locations.clone(),
+            slab: self.slab,
+        }
+    }
+}
+
+impl fmt::Debug for Iter<'_> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("Iter")
+            .field("remaining", &self.locations.len())
+            .finish()
+    }
+}
@@ -1,20 +1,130 @@
+use core::fmt;
+
 use crate::{Entry, Slab};
 
-// Building `Slab` from pairs (usize, T).
-pub(crate) struct Builder<T> {
+/// Build a `Slab` from values with known keys.
+///
+/// This is what `FromIterator<(usize, T)>` and deserialization use internally.
+/// The keys do not need to be inserted in order, but the vacant list can only
+/// be built incrementally while they are. Otherwise `build` has to recreate
+/// it in O(n) time.
+///
+/// Note that the built slab will use space proportional to the largest key.
+///
+/// # Examples
+///
+/// ```
+/// # use slab::*;
+/// let mut builder = SlabBuilder::with_capacity(4);
+/// builder.insert_at(1, 'a').unwrap();
+/// builder.insert_at(3, 'b').unwrap();
+/// assert!(!builder.vacant_list_broken());
+///
+/// let slab = builder.build();
+/// assert_eq!(slab[1], 'a');
+/// assert_eq!(slab[3], 'b');
+/// assert_eq!(slab.len(), 2);
+/// ```
+#[derive(Debug)]
+pub struct SlabBuilder<T> {
     slab: Slab<T>,
+    duplicate_keys: DuplicateKeys,
     vacant_list_broken: bool,
     first_vacant_index: Option<usize>,
 }
 
-impl<T> Builder<T> {
-    pub(crate) fn with_capacity(capacity: usize) -> Self {
+/// What `SlabBuilder::insert_at` does with a key that was already inserted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DuplicateKeys {
+    /// Replace the previous value. This is consistent with `HashMap` and
+    /// `BTreeMap`.
+    #[default]
+    Replace,
+    /// Keep the previous value and drop the new one.
+    KeepFirst,
+    /// Return a [`DuplicateKeyError`] holding the new value.
+    Error,
+}
+
+/// The error returned by `SlabBuilder::insert_at` for a duplicate key when
+/// the builder uses [`DuplicateKeys::Error`].
+#[derive(Debug)]
+pub struct DuplicateKeyError<T> {
+    key: usize,
+    value: T,
+}
+
+impl<T> SlabBuilder<T> {
+    /// Construct a new builder with no values.
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    /// Construct a new builder for a slab with the specified capacity.
+    pub fn with_capacity(capacity: usize) -> Self {
         Self {
             slab: Slab::with_capacity(capacity),
+            duplicate_keys: DuplicateKeys::Replace,
             vacant_list_broken: false,
             first_vacant_index: None,
         }
     }
+
+    /// Set what happens when a key is inserted more than once.
+    ///
+    /// The default is [`DuplicateKeys::Replace`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use slab::*;
+    /// let mut builder = SlabBuilder::new().duplicate_keys(DuplicateKeys::Error);
+    /// builder.insert_at(0, "hello").unwrap();
+    ///
+    /// let err = builder.insert_at(0, "world").unwrap_err();
+    /// assert_eq!(err.key(), 0);
+    /// assert_eq!(err.into_value(), "world");
+    /// ```
+    pub fn duplicate_keys(mut self, duplicate_keys: DuplicateKeys) -> Self {
+        self.duplicate_keys = duplicate_keys;
+        self
+    }
+
+    /// Return the number of values inserted so far.
+    pub fn len(&self) -> usize {
+        self.slab.len
+    }
+
+    /// Return `true` if no values have been inserted.
+    pub fn is_empty(&self) -> bool {
+        self.slab.len == 0
+    }
+
+    /// Return `true` if `build` will have to recreate the vacant list.
+    ///
+    /// This happens once a key is inserted below the largest key inserted so
+    /// far, unless no entries end up vacant.
+    pub fn vacant_list_broken(&self) -> bool {
+        self.vacant_list_broken && self.slab.len != self.slab.entries.len()
+    }
+
+    /// Insert a value with the given key.
+    ///
+    /// If the key was inserted before, the [`DuplicateKeys`] setting of the
+    /// builder determines the outcome.
+    pub fn insert_at(&mut self, key: usize, value: T) -> Result<(), DuplicateKeyError<T>> {
+        if let Some(Entry::Occupied(_)) = self.slab.entries.get(key) {
+            match self.duplicate_keys {
+                DuplicateKeys::Replace => {}
+                DuplicateKeys::KeepFirst => return Ok(()),
+                DuplicateKeys::Error => return Err(DuplicateKeyError { key, value }),
+            }
+        }
+        self.pair(key, value);
+        Ok(())
+    }
+
+    // Insert a value, replacing any previous value with the same key.
     pub(crate) fn pair(&mut self, key: usize, value: T) {
         let slab = &mut self.slab;
         if key < slab.entries.len() {
@@ -42,13 +152,40 @@ impl<T> Builder<T> {
         }
     }
 
-    pub(crate) fn build(self) -> Slab<T> {
+    /// Build the slab.
+    ///
+    /// If keys were inserted out of order, this recreates the vacant list so
+    /// that lower keys are reused first. Use
+    /// [`build_with_report`](SlabBuilder::build_with_report) to find out
+    /// whether it did.
+    pub fn build(self) -> Slab<T> {
+        self.build_with_report().0
+    }
+
+    /// Build the slab, and report whether the vacant list had to be
+    /// recreated because keys were inserted out of order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use slab::*;
+    /// let mut builder = SlabBuilder::new();
+    /// builder.insert_at(3, 'a').unwrap();
+    /// builder.insert_at(1, 'b').unwrap();
+    ///
+    /// let (slab, recreated) = builder.build_with_report();
+    /// assert!(recreated);
+    /// assert_eq!(slab.vacant_key(), 0);
+    /// ```
+    pub fn build_with_report(self) -> (Slab<T>, bool) {
         let mut slab = self.slab;
+        let mut recreated = false;
         if slab.len == slab.entries.len() {
             // no vacant entries, so next might not have been updated
             slab.next = slab.entries.len();
         } else if self.vacant_list_broken {
             slab.recreate_vacant_list();
+            recreated = true;
         } else if let Some(first_vacant_index) = self.first_vacant_index {
             let next = slab.entries.len();
             match &mut slab.entries[first_vacant_index] {
@@ -58,6 +195,33 @@ impl<T> Builder<T> {
         } else {
             unreachable!()
         }
-        slab
+        (slab, recreated)
     }
 }
+
+impl<T> Default for SlabBuilder<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> DuplicateKeyError<T> {
+    /// Return the key that was inserted twice.
+    pub fn key(&self) -> usize {
+        self.key
+    }
+
+    /// Return the rejected value.
+    pub fn into_value(self) -> T {
+        self.value
+    }
+}
+
+impl<T> fmt::Display for DuplicateKeyError<T> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(fmt, "duplicate key {}", self.key)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: fmt::Debug> std::error::Error for DuplicateKeyError<T> {}
@@ -0,0 +1,262 @@
+//! A slab that keeps its values packed together.
+//!
+//! [`DenseSlab`] is a sparse set: the values are stored contiguously in a
+//! dense `Vec<T>`, and a [`Slab`] maps each key to the value's index in it.
+//! Removing a value moves the last value into its place, so iteration never
+//! has to skip vacant entries and all values are available as one slice, at
+//! the cost of an extra indirection on lookup and of not preserving the order
+//! of the values.
+//!
+//! # Examples
+//!
+//! ```
+//! use slab::dense::DenseSlab;
+//!
+//! let mut slab = DenseSlab::new();
+//! let a = slab.insert(1.0);
+//! let b = slab.insert(2.0);
+//! let c = slab.insert(3.0);
+//!
+//! slab.remove(a);
+//! assert_eq!(slab.as_slice(), [3.0, 2.0]);
+//! assert_eq!(slab[c], 3.0);
+//!
+//! for value in slab.as_mut_slice() {
+//!     *value *= 2.0;
+//! }
+//! assert_eq!(slab[b], 4.0);
+//! ```
+
+use alloc::vec::Vec;
+use core::iter::{Copied, Zip};
+use core::{fmt, ops, slice};
+
+use crate::Slab;
+
+/// Storage with stable keys whose values are contiguous in memory.
+///
+/// See the [module documentation] for more details.
+///
+/// [module documentation]: index.html
+pub struct DenseSlab<T> {
+    // Index into `dense` for every key
+    sparse: Slab<usize>,
+    dense: Vec<T>,
+
+    // Key of every value in `dense`
+    keys: Vec<usize>,
+}
+
+/// An iterator over the keys and values of a [`DenseSlab`] in dense order.
+pub type Iter<'a, T> = Zip<Copied<slice::Iter<'a, usize>>, slice::Iter<'a, T>>;
+
+/// A mutable iterator over the keys and values of a [`DenseSlab`] in dense
+/// order.
+pub type IterMut<'a, T> = Zip<Copied<slice::Iter<'a, usize>>, slice::IterMut<'a, T>>;
+
+impl<T> DenseSlab<T> {
+    /// Construct a new, empty `DenseSlab`.
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    /// Construct a new, empty `DenseSlab` with the specified capacity.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            sparse: Slab::with_capacity(capacity),
+            dense: Vec::with_capacity(capacity),
+            keys: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Return the number of values the slab can store without reallocating.
+    pub fn capacity(&self) -> usize {
+        self.dense.capacity()
+    }
+
+    /// Reserve capacity for at least `additional` more values.
+    pub fn reserve(&mut self, additional: usize) {
+        self.sparse.reserve(additional);
+        self.dense.reserve(additional);
+        self.keys.reserve(additional);
+    }
+
+    /// Return the number of stored values.
+    pub fn len(&self) -> usize {
+        self.dense.len()
+    }
+
+    /// Return `true` if there are no values stored in the slab.
+    pub fn is_empty(&self) -> bool {
+        self.dense.is_empty()
+    }
+
+    /// Remove all values from the slab.
+    pub fn clear(&mut self) {
+        self.sparse.clear();
+        self.dense.clear();
+        self.keys.clear();
+    }
+
+    /// Return `true` if a value is associated with the given key.
+    pub fn contains(&self, key: usize) -> bool {
+        self.sparse.contains(key)
+    }
+
+    /// Return a reference to the value associated with the given key.
+    ///
+    /// If the given key is not associated with a value, then `None` is
+    /// returned.
+    pub fn get(&self, key: usize) -> Option<&T> {
+        let index = *self.sparse.get(key)?;
+        Some(&self.dense[index])
+    }
+
+    /// Return a mutable reference to the value associated with the given key.
+    ///
+    /// If the given key is not associated with a value, then `None` is
+    /// returned.
+    pub fn get_mut(&mut self, key: usize) -> Option<&mut T> {
+        let index = *self.sparse.get(key)?;
+        Some(&mut self.dense[index])
+    }
+
+    /// Return the index in [`as_slice`](DenseSlab::as_slice) of the value
+    /// associated with the given key.
+    ///
+    /// The index changes when other values are removed.
+    pub fn index_of(&self, key: usize) -> Option<usize> {
+        self.sparse.get(key).copied()
+    }
+
+    /// Insert a value in the slab, returning the key assigned to the value.
+    ///
+    /// The value is appended to the dense values.
+    pub fn insert(&mut self, value: T) -> usize {
+        let key = self.sparse.insert(self.dense.len());
+        self.dense.push(value);
+        self.keys.push(key);
+        key
+    }
+
+    /// Remove and return the value associated with the given key.
+    ///
+    /// The last dense value is moved into the place of the removed one. If
+    /// the given key is not associated with a value, then `None` is returned.
+    pub fn try_remove(&mut self, key: usize) -> Option<T> {
+        let index = self.sparse.try_remove(key)?;
+        let value = self.dense.swap_remove(index);
+        self.keys.swap_remove(index);
+        if let Some(&moved) = self.keys.get(index) {
+            self.sparse[moved] = index;
+        }
+        Some(value)
+    }
+
+    /// Remove and return the value associated with the given key.
+    ///
+    /// The last dense value is moved into the place of the removed one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `key` is not associated with a value.
+    #[cfg_attr(not(slab_no_track_caller), track_caller)]
+    pub fn remove(&mut self, key: usize) -> T {
+        self.try_remove(key).expect("invalid key")
+    }
+
+    /// Return the values as a contiguous slice.
+    pub fn as_slice(&self) -> &[T] {
+        &self.dense
+    }
+
+    /// Return the values as a contiguous mutable slice.
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        &mut self.dense
+    }
+
+    /// Return the keys of the values, in the same order as
+    /// [`as_slice`](DenseSlab::as_slice).
+    pub fn keys(&self) -> &[usize] {
+        &self.keys
+    }
+
+    /// Return an iterator over the keys and values in dense order.
+    pub fn iter(&self) -> Iter<'_, T> {
+        self.keys.iter().copied().zip(self.dense.iter())
+    }
+
+    /// Return a mutable iterator over the keys and values in dense order.
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        self.keys.iter().copied().zip(self.dense.iter_mut())
+    }
+}
+
+impl<T> Default for DenseSlab<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Clone for DenseSlab<T>
+where
+    T: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            sparse: self.sparse.clone(),
+            dense: self.dense.clone(),
+            keys: self.keys.clone(),
+        }
+    }
+}
+
+impl<T> ops::Index<usize> for DenseSlab<T> {
+    type Output = T;
+
+    #[cfg_attr(not(slab_no_track_caller), track_caller)]
+    fn index(&self, key: usize) -> &T {
+        self.get(key).expect("invalid key")
+    }
+}
+
+impl<T> ops::IndexMut<usize> for DenseSlab<T> {
+    #[cfg_attr(not(slab_no_track_caller), track_caller)]
+    fn index_mut(&mut self, key: usize) -> &mut T {
+        self.get_mut(key).expect("invalid key")
+    }
+}
+
+impl<'a, T> IntoIterator for &'a DenseSlab<T> {
+    type Item = (usize, &'a T);
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut DenseSlab<T> {
+    type Item = (usize, &'a mut T);
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> IterMut<'a, T> {
+        self.iter_mut()
+    }
+}
+
+impl<T> fmt::Debug for DenseSlab<T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if fmt.alternate() {
+            fmt.debug_map().entries(self.iter()).finish()
+        } else {
+            fmt.debug_struct("DenseSlab")
+                .field("len", &self.len())
+                .field("cap", &self.capacity())
+                .finish()
+        }
+    }
+}
@@ -0,0 +1,51 @@
+use core::fmt;
+
+/// The error returned by the `Result`-returning lookups of `Slab`, such as
+/// [`try_get`](crate::Slab::try_get) and [`try_key_of`](crate::Slab::try_key_of).
+///
+/// # Examples
+///
+/// ```
+/// # use slab::*;
+/// let mut slab = Slab::new();
+/// let key = slab.insert("hello");
+/// slab.insert("world");
+/// slab.remove(key);
+///
+/// assert_eq!(slab.try_get(key), Err(SlabError::Vacant(key)));
+/// assert_eq!(slab.try_get(5), Err(SlabError::OutOfBounds(5)));
+/// assert_eq!(slab.try_get(5).unwrap_err().to_string(), "key 5 is out of bounds");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SlabError {
+    /// The key is not below the number of entries in the slab, so it has
+    /// never been handed out.
+    OutOfBounds(usize),
+    /// The key has been handed out, but its value was removed.
+    Vacant(usize),
+    /// The reference does not point to a value in the slab.
+    ForeignReference,
+    /// The capacity would exceed `isize::MAX` bytes, or the allocator could
+    /// not provide it.
+    CapacityOverflow,
+    /// The same key was passed where distinct keys are required.
+    SameKey(usize),
+}
+
+impl fmt::Display for SlabError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            SlabError::OutOfBounds(key) => write!(fmt, "key {} is out of bounds", key),
+            SlabError::Vacant(key) => write!(fmt, "key {} is vacant", key),
+            SlabError::ForeignReference => {
+                write!(fmt, "the reference points to a value outside this slab")
+            }
+            SlabError::CapacityOverflow => write!(fmt, "capacity overflow"),
+            SlabError::SameKey(key) => write!(fmt, "key {} was passed twice", key),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SlabError {}
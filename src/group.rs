@@ -0,0 +1,255 @@
+//! Several slabs that share their keys.
+//!
+//! A [`SlabGroup`] stores a tuple of values as columns, one [`Slab`] per
+//! tuple element. Every insertion and removal is applied to all columns, so
+//! their vacant lists stay in lockstep and a value's key is the same in every
+//! column. This is useful for entity-component layouts, where code that only
+//! touches one component can iterate over that column alone.
+//!
+//! # Examples
+//!
+//! ```
+//! use slab::group::SlabGroup;
+//!
+//! let mut entities: SlabGroup<(&str, f32, u32)> = SlabGroup::new();
+//! let player = entities.insert(("player", 1.5, 100));
+//! let enemy = entities.insert(("enemy", 3.0, 50));
+//!
+//! let (_, _, health) = entities.get_mut(enemy).unwrap();
+//! *health -= 20;
+//!
+//! // Iterate over a single column
+//! let (_, positions, _) = entities.columns();
+//! let total: f32 = positions.iter().map(|(_, &pos)| pos).sum();
+//! assert_eq!(total, 4.5);
+//!
+//! assert_eq!(entities.remove(player), ("player", 1.5, 100));
+//! assert_eq!(entities.get(enemy), Some((&"enemy", &3.0, &30)));
+//! ```
+
+use core::fmt;
+
+use crate::Slab;
+
+/// A tuple of values that a [`SlabGroup`] stores in separate columns.
+///
+/// This is implemented for tuples of up to eight elements.
+pub trait Columns: Sized {
+    type Slabs;
+    type Ref<'a>
+    where
+        Self: 'a;
+    type Mut<'a>
+    where
+        Self: 'a;
+    fn with_capacity(capacity: usize) -> Self::Slabs;
+    fn len(slabs: &Self::Slabs) -> usize;
+    fn capacity(slabs: &Self::Slabs) -> usize;
+    fn insert(slabs: &mut Self::Slabs, values: Self) -> usize;
+    fn try_remove(slabs: &mut Self::Slabs, key: usize) -> Option<Self>;
+    fn get(slabs: &Self::Slabs, key: usize) -> Option<Self::Ref<'_>>;
+    fn get_mut(slabs: &mut Self::Slabs, key: usize) -> Option<Self::Mut<'_>>;
+    fn contains(slabs: &Self::Slabs, key: usize) -> bool;
+    fn reserve(slabs: &mut Self::Slabs, additional: usize);
+    fn clear(slabs: &mut Self::Slabs);
+}
+
+/// Slabs for each element of a tuple, sharing their keys.
+///
+/// See the [module documentation] for more details.
+///
+/// [module documentation]: index.html
+pub struct SlabGroup<C: Columns> {
+    slabs: C::Slabs,
+}
+
+impl<C: Columns> SlabGroup<C> {
+    /// Construct a new, empty `SlabGroup`.
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    /// Construct a new, empty `SlabGroup` with the specified capacity in
+    /// every column.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            slabs: C::with_capacity(capacity),
+        }
+    }
+
+    /// Return the number of values each column can store without
+    /// reallocating.
+    pub fn capacity(&self) -> usize {
+        C::capacity(&self.slabs)
+    }
+
+    /// Reserve capacity for at least `additional` more values in every
+    /// column.
+    pub fn reserve(&mut self, additional: usize) {
+        C::reserve(&mut self.slabs, additional);
+    }
+
+    /// Return the number of stored tuples.
+    pub fn len(&self) -> usize {
+        C::len(&self.slabs)
+    }
+
+    /// Return `true` if there are no tuples stored.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Remove all tuples.
+    pub fn clear(&mut self) {
+        C::clear(&mut self.slabs);
+    }
+
+    /// Return `true` if a tuple is associated with the given key.
+    pub fn contains(&self, key: usize) -> bool {
+        C::contains(&self.slabs, key)
+    }
+
+    /// Insert a tuple, storing each element in its column under the same
+    /// key, and return that key.
+    pub fn insert(&mut self, values: C) -> usize {
+        C::insert(&mut self.slabs, values)
+    }
+
+    /// Return references to the elements associated with the given key.
+    ///
+    /// If the given key is not associated with a tuple, then `None` is
+    /// returned.
+    pub fn get(&self, key: usize) -> Option<C::Ref<'_>> {
+        C::get(&self.slabs, key)
+    }
+
+    /// Return mutable references to the elements associated with the given
+    /// key.
+    ///
+    /// If the given key is not associated with a tuple, then `None` is
+    /// returned.
+    pub fn get_mut(&mut self, key: usize) -> Option<C::Mut<'_>> {
+        C::get_mut(&mut self.slabs, key)
+    }
+
+    /// Remove and return the tuple associated with the given key from every
+    /// column.
+    ///
+    /// If the given key is not associated with a tuple, then `None` is
+    /// returned.
+    pub fn try_remove(&mut self, key: usize) -> Option<C> {
+        C::try_remove(&mut self.slabs, key)
+    }
+
+    /// Remove and return the tuple associated with the given key from every
+    /// column.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `key` is not associated with a tuple.
+    #[cfg_attr(not(slab_no_track_caller), track_caller)]
+    pub fn remove(&mut self, key: usize) -> C {
+        self.try_remove(key).expect("invalid key")
+    }
+
+    /// Return the column slabs.
+    ///
+    /// Only shared access is provided, because inserting into or removing
+    /// from a single column would break the shared keys.
+    pub fn columns(&self) -> &C::Slabs {
+        &self.slabs
+    }
+}
+
+impl<C: Columns> Default for SlabGroup<C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C: Columns> fmt::Debug for SlabGroup<C> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("SlabGroup")
+            .field("len", &self.len())
+            .field("cap", &self.capacity())
+            .finish()
+    }
+}
+
+macro_rules! impl_columns {
+    ($first:ident $(, $rest:ident)*; $first_idx:tt $(, $idx:tt)*) => {
+        impl<$first $(, $rest)*> Columns for ($first, $($rest,)*) {
+            type Slabs = (Slab<$first>, $(Slab<$rest>,)*);
+            type Ref<'a> = (&'a $first, $(&'a $rest,)*)
+            where
+                Self: 'a;
+            type Mut<'a> = (&'a mut $first, $(&'a mut $rest,)*)
+            where
+                Self: 'a;
+
+            fn with_capacity(capacity: usize) -> Self::Slabs {
+                (
+                    Slab::<$first>::with_capacity(capacity),
+                    $(Slab::<$rest>::with_capacity(capacity),)*
+                )
+            }
+
+            fn len(slabs: &Self::Slabs) -> usize {
+                slabs.$first_idx.len()
+            }
+
+            fn capacity(slabs: &Self::Slabs) -> usize {
+                let capacity = slabs.$first_idx.capacity();
+                $(let capacity = capacity.min(slabs.$idx.capacity());)*
+                capacity
+            }
+
+            fn insert(slabs: &mut Self::Slabs, values: Self) -> usize {
+                let key = slabs.$first_idx.insert(values.$first_idx);
+                $(
+                    let other = slabs.$idx.insert(values.$idx);
+                    debug_assert_eq!(other, key);
+                )*
+                key
+            }
+
+            fn try_remove(slabs: &mut Self::Slabs, key: usize) -> Option<Self> {
+                let first = slabs.$first_idx.try_remove(key)?;
+                Some((first, $(slabs.$idx.remove(key),)*))
+            }
+
+            fn get(slabs: &Self::Slabs, key: usize) -> Option<Self::Ref<'_>> {
+                let first = slabs.$first_idx.get(key)?;
+                Some((first, $(&slabs.$idx[key],)*))
+            }
+
+            fn get_mut(slabs: &mut Self::Slabs, key: usize) -> Option<Self::Mut<'_>> {
+                let first = slabs.$first_idx.get_mut(key)?;
+                Some((first, $(&mut slabs.$idx[key],)*))
+            }
+
+            fn contains(slabs: &Self::Slabs, key: usize) -> bool {
+                slabs.$first_idx.contains(key)
+            }
+
+            fn reserve(slabs: &mut Self::Slabs, additional: usize) {
+                slabs.$first_idx.reserve(additional);
+                $(slabs.$idx.reserve(additional);)*
+            }
+
+            fn clear(slabs: &mut Self::Slabs) {
+                slabs.$first_idx.clear();
+                $(slabs.$idx.clear();)*
+            }
+        }
+    };
+}
+
+impl_columns!(A; 0);
+impl_columns!(A, B; 0, 1);
+impl_columns!(A, B, C; 0, 1, 2);
+impl_columns!(A, B, C, D; 0, 1, 2, 3);
+impl_columns!(A, B, C, D, E; 0, 1, 2, 3, 4);
+impl_columns!(A, B, C, D, E, F; 0, 1, 2, 3, 4, 5);
+impl_columns!(A, B, C, D, E, F, G; 0, 1, 2, 3, 4, 5, 6);
+impl_columns!(A, B, C, D, E, F, G, H; 0, 1, 2, 3, 4, 5, 6, 7);
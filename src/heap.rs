@@ -0,0 +1,234 @@
+//! A priority queue whose values can be changed or removed by key.
+//!
+//! [`SlabHeap`] is a binary max-heap like `BinaryHeap`, except that every
+//! value is stored in a slab and identified by its stable slab key. The heap
+//! itself only orders keys, and each value remembers its position in the heap,
+//! so a value can be reprioritized or removed in O(log n) time given its key.
+//! This is what decrease-key in Dijkstra's algorithm or cancelling a timer
+//! needs.
+//!
+//! # Examples
+//!
+//! ```
+//! use slab::heap::SlabHeap;
+//! use std::cmp::Reverse;
+//!
+//! // A min-heap of distances
+//! let mut heap = SlabHeap::new();
+//! let a = heap.push(Reverse(7));
+//! let b = heap.push(Reverse(3));
+//! let c = heap.push(Reverse(5));
+//!
+//! // Found a shorter path to `a`
+//! heap.change_priority(a, |dist| dist.0 = 1);
+//! heap.remove(c);
+//!
+//! assert_eq!(heap.pop(), Some((a, Reverse(1))));
+//! assert_eq!(heap.pop(), Some((b, Reverse(3))));
+//! assert_eq!(heap.pop(), None);
+//! ```
+
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::Slab;
+
+/// A binary max-heap with stable keys.
+///
+/// See the [module documentation] for more details.
+///
+/// [module documentation]: index.html
+pub struct SlabHeap<T> {
+    nodes: Slab<Node<T>>,
+
+    // Keys of `nodes` in heap order
+    heap: Vec<usize>,
+}
+
+struct Node<T> {
+    value: T,
+
+    // Position of the key in `heap`
+    pos: usize,
+}
+
+impl<T: Ord> SlabHeap<T> {
+    /// Construct a new, empty `SlabHeap`.
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    /// Construct a new, empty `SlabHeap` with the specified capacity.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            nodes: Slab::with_capacity(capacity),
+            heap: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Return the number of values in the heap.
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    /// Return `true` if the heap is empty.
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    /// Remove all values from the heap.
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+        self.heap.clear();
+    }
+
+    /// Return `true` if a value is associated with the given key.
+    pub fn contains(&self, key: usize) -> bool {
+        self.nodes.contains(key)
+    }
+
+    /// Return a reference to the value associated with the given key.
+    ///
+    /// If the given key is not associated with a value, then `None` is
+    /// returned.
+    pub fn get(&self, key: usize) -> Option<&T> {
+        self.nodes.get(key).map(|node| &node.value)
+    }
+
+    /// Push a value onto the heap, returning the key assigned to it.
+    pub fn push(&mut self, value: T) -> usize {
+        let pos = self.heap.len();
+        let key = self.nodes.insert(Node { value, pos });
+        self.heap.push(key);
+        self.sift_up(pos);
+        key
+    }
+
+    /// Return the key and a reference to the greatest value in the heap.
+    pub fn peek(&self) -> Option<(usize, &T)> {
+        let key = *self.heap.first()?;
+        Some((key, &self.nodes[key].value))
+    }
+
+    /// Remove the greatest value from the heap and return it with its key.
+    pub fn pop(&mut self) -> Option<(usize, T)> {
+        let key = *self.heap.first()?;
+        Some((key, self.remove(key)))
+    }
+
+    /// Modify the value associated with the given key with `f`, and restore
+    /// the heap order afterwards.
+    ///
+    /// The value may be made greater or smaller.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `key` is not associated with a value. If `f` panics, the
+    /// value stays in the heap but the heap order is not restored.
+    #[cfg_attr(not(slab_no_track_caller), track_caller)]
+    pub fn change_priority<F>(&mut self, key: usize, f: F)
+    where
+        F: FnOnce(&mut T),
+    {
+        let node = self.nodes.get_mut(key).expect("invalid key");
+        f(&mut node.value);
+        let pos = node.pos;
+        let pos = self.sift_up(pos);
+        self.sift_down(pos);
+    }
+
+    /// Remove and return the value associated with the given key.
+    ///
+    /// If the given key is not associated with a value, then `None` is
+    /// returned.
+    pub fn try_remove(&mut self, key: usize) -> Option<T> {
+        let Node { value, pos } = self.nodes.try_remove(key)?;
+
+        // Fill the hole with the last key and move it to where it belongs
+        let last = self.heap.pop().unwrap();
+        if pos < self.heap.len() {
+            self.heap[pos] = last;
+            self.nodes[last].pos = pos;
+            let pos = self.sift_up(pos);
+            self.sift_down(pos);
+        }
+        Some(value)
+    }
+
+    /// Remove and return the value associated with the given key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `key` is not associated with a value.
+    #[cfg_attr(not(slab_no_track_caller), track_caller)]
+    pub fn remove(&mut self, key: usize) -> T {
+        self.try_remove(key).expect("invalid key")
+    }
+
+    /// Return an iterator over the keys and values in arbitrary order.
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = (usize, &T)> + '_ {
+        self.nodes.iter().map(|(key, node)| (key, &node.value))
+    }
+
+    // Move the key at `pos` towards the root while it is greater than its
+    // parent, returning its new position.
+    fn sift_up(&mut self, mut pos: usize) -> usize {
+        while pos > 0 {
+            let parent = (pos - 1) / 2;
+            if self.value_at(pos) <= self.value_at(parent) {
+                break;
+            }
+            self.swap(pos, parent);
+            pos = parent;
+        }
+        pos
+    }
+
+    // Move the key at `pos` towards the leaves while it is smaller than one
+    // of its children.
+    fn sift_down(&mut self, mut pos: usize) {
+        loop {
+            let left = 2 * pos + 1;
+            let right = left + 1;
+            let mut greatest = pos;
+            if left < self.heap.len() && self.value_at(left) > self.value_at(greatest) {
+                greatest = left;
+            }
+            if right < self.heap.len() && self.value_at(right) > self.value_at(greatest) {
+                greatest = right;
+            }
+            if greatest == pos {
+                break;
+            }
+            self.swap(pos, greatest);
+            pos = greatest;
+        }
+    }
+
+    fn value_at(&self, pos: usize) -> &T {
+        &self.nodes[self.heap[pos]].value
+    }
+
+    fn swap(&mut self, a: usize, b: usize) {
+        self.heap.swap(a, b);
+        self.nodes[self.heap[a]].pos = a;
+        self.nodes[self.heap[b]].pos = b;
+    }
+}
+
+impl<T: Ord> Default for SlabHeap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> fmt::Debug for SlabHeap<T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_list()
+            .entries(self.heap.iter().map(|&key| &self.nodes[key].value))
+            .finish()
+    }
+}
@@ -0,0 +1,334 @@
+//! A slab whose values can be initialized in place.
+//!
+//! Inserting a large value into a [`Slab`] moves it into its
+//! entry, often through the stack. [`InPlaceSlab::insert_uninit`] instead
+//! hands out the uninitialized slot of a vacant entry, so that the value is
+//! built directly in the slab's memory.
+//!
+//! An `InPlaceSlab` tracks which keys are occupied with a `Slab<()>` and
+//! stores the values in a separate [`Column`], like the slabs generated by
+//! [`SlabSoA`](crate::soa), so keys are handed out and reused exactly like
+//! they are by `Slab`. Each value thus takes the space of a `Slab<()>` entry
+//! in addition to its own, where a `Slab` can often fold its entry tag into a
+//! niche of the value.
+//!
+//! # Examples
+//!
+//! ```
+//! use slab::inplace::InPlaceSlab;
+//!
+//! let mut slab = InPlaceSlab::new();
+//! let key = slab.insert_uninit(|key, slot| slot.write([key as u8; 4096]));
+//! assert_eq!(slab[key][0], 0);
+//!
+//! slab.remove(key);
+//! assert_eq!(slab.insert([1; 4096]), key);
+//! ```
+
+use core::mem::MaybeUninit;
+use core::{fmt, ops, ptr};
+
+use crate::soa::{Column, ColumnIter, ColumnIterMut};
+use crate::Slab;
+
+/// Pre-allocated storage for a uniform data type, with in-place
+/// initialization of values.
+///
+/// See the [module documentation] for more details.
+///
+/// [module documentation]: index.html
+pub struct InPlaceSlab<T> {
+    // Tracks which keys are occupied and links the vacant ones
+    keys: Slab<()>,
+
+    // The value of every occupied key
+    values: Column<T>,
+}
+
+/// An iterator over the values stored in an [`InPlaceSlab`].
+pub type Iter<'a, T> = ColumnIter<'a, T>;
+
+/// A mutable iterator over the values stored in an [`InPlaceSlab`].
+pub type IterMut<'a, T> = ColumnIterMut<'a, T>;
+
+impl<T> InPlaceSlab<T> {
+    /// Construct a new, empty `InPlaceSlab`.
+    pub const fn new() -> Self {
+        Self {
+            keys: Slab::new(),
+            values: Column::new_vec(),
+        }
+    }
+
+    /// Construct a new, empty `InPlaceSlab` with the specified capacity.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            keys: Slab::with_capacity(capacity),
+            values: Column::with_capacity(capacity),
+        }
+    }
+
+    /// Return the number of values the slab can store without reallocating.
+    pub fn capacity(&self) -> usize {
+        self.keys.capacity().min(self.values.capacity())
+    }
+
+    /// Reserve capacity for at least `additional` more values.
+    ///
+    /// Vacant entries count towards the reserved capacity.
+    pub fn reserve(&mut self, additional: usize) {
+        self.keys.reserve(additional);
+        self.values.reserve(additional);
+    }
+
+    /// Return the number of stored values.
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Return `true` if there are no values stored in the slab.
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Remove all values from the slab.
+    pub fn clear(&mut self) {
+        // Vacates every key once the values are dropped, or when a destructor
+        // panics, which leaks the values that are left
+        struct ClearGuard<'a>(&'a mut Slab<()>);
+        impl Drop for ClearGuard<'_> {
+            fn drop(&mut self) {
+                self.0.clear();
+            }
+        }
+
+        let keys = ClearGuard(&mut self.keys);
+        for (key, ()) in keys.0.iter() {
+            // Safety: the key is occupied, and vacated by the guard before
+            // its slot is written again
+            unsafe { ptr::drop_in_place(self.values.get_mut(key)) };
+        }
+    }
+
+    /// Return `true` if a value is associated with the given key.
+    pub fn contains(&self, key: usize) -> bool {
+        self.keys.contains(key)
+    }
+
+    /// Return a reference to the value associated with the given key.
+    ///
+    /// If the given key is not associated with a value, then `None` is
+    /// returned.
+    pub fn get(&self, key: usize) -> Option<&T> {
+        if self.keys.contains(key) {
+            // Safety: the key is occupied
+            Some(unsafe { self.values.get(key) })
+        } else {
+            None
+        }
+    }
+
+    /// Return a mutable reference to the value associated with the given key.
+    ///
+    /// If the given key is not associated with a value, then `None` is
+    /// returned.
+    pub fn get_mut(&mut self, key: usize) -> Option<&mut T> {
+        if self.keys.contains(key) {
+            // Safety: the key is occupied
+            Some(unsafe { self.values.get_mut(key) })
+        } else {
+            None
+        }
+    }
+
+    /// Return the key of the next vacant entry.
+    pub fn vacant_key(&self) -> usize {
+        self.keys.vacant_key()
+    }
+
+    /// Insert a value in the slab, returning the key assigned to the value.
+    pub fn insert(&mut self, value: T) -> usize {
+        let key = self.keys.vacant_key();
+        self.values.write(key, value);
+        self.keys.insert(());
+        key
+    }
+
+    /// Insert a value that is initialized in place, returning the key
+    /// assigned to it.
+    ///
+    /// `init` is called with the key and the uninitialized slot for the
+    /// value, and must return the reference it got from initializing the
+    /// slot, usually with `MaybeUninit::write` or `MaybeUninit::assume_init_mut`.
+    /// Large values can be built field by field through
+    /// `MaybeUninit::as_mut_ptr`, without being moved into the slab afterwards.
+    ///
+    /// The key is only marked as occupied once `init` has returned, so if it
+    /// panics, the slab is left as it was and a partially initialized value
+    /// is leaked.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `init` returns a reference to something other than the
+    /// slot.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use slab::inplace::InPlaceSlab;
+    /// use std::ptr::addr_of_mut;
+    ///
+    /// struct Buffer {
+    ///     key: usize,
+    ///     data: [u8; 16 * 1024],
+    /// }
+    ///
+    /// let mut slab = InPlaceSlab::new();
+    /// let key = slab.insert_uninit(|key, slot| {
+    ///     let buffer: *mut Buffer = slot.as_mut_ptr();
+    ///     // Safety: every field is written before the value is assumed to
+    ///     // be initialized
+    ///     unsafe {
+    ///         addr_of_mut!((*buffer).key).write(key);
+    ///         addr_of_mut!((*buffer).data).write_bytes(0, 1);
+    ///         slot.assume_init_mut()
+    ///     }
+    /// });
+    ///
+    /// assert_eq!(slab[key].key, key);
+    /// assert!(slab[key].data.iter().all(|&byte| byte == 0));
+    /// ```
+    #[cfg_attr(not(slab_no_track_caller), track_caller)]
+    pub fn insert_uninit<F>(&mut self, init: F) -> usize
+    where
+        F: for<'a> FnOnce(usize, &'a mut MaybeUninit<T>) -> &'a mut T,
+    {
+        let key = self.keys.vacant_key();
+        let slot = self.values.slot(key);
+        let slot_ptr: *const MaybeUninit<T> = slot;
+        let value: *const T = init(key, slot);
+        assert!(
+            ptr::eq(value, slot_ptr.cast()),
+            "`insert_uninit` initializer must return the reference to its slot"
+        );
+
+        // A reference to the slot can only be created by initializing it, so
+        // the slot now holds a valid `T`
+        self.keys.insert(());
+        key
+    }
+
+    /// Remove and return the value associated with the given key.
+    ///
+    /// The key is then released and may be associated with future stored
+    /// values. If the given key is not associated with a value, then `None`
+    /// is returned.
+    pub fn try_remove(&mut self, key: usize) -> Option<T> {
+        self.keys.try_remove(key)?;
+        // Safety: the key was occupied
+        Some(unsafe { self.values.take(key) })
+    }
+
+    /// Remove and return the value associated with the given key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `key` is not associated with a value.
+    #[cfg_attr(not(slab_no_track_caller), track_caller)]
+    pub fn remove(&mut self, key: usize) -> T {
+        self.try_remove(key).expect("invalid key")
+    }
+
+    /// Return an iterator over the keys and values in key order.
+    pub fn iter(&self) -> Iter<'_, T> {
+        // Safety: every key returned by the iterator is occupied
+        unsafe { ColumnIter::new(self.keys.iter(), &self.values) }
+    }
+
+    /// Return a mutable iterator over the keys and values in key order.
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        // Safety: every key returned by the iterator is occupied
+        unsafe { ColumnIterMut::new(self.keys.iter(), &mut self.values) }
+    }
+}
+
+impl<T> Drop for InPlaceSlab<T> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T> Default for InPlaceSlab<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Clone for InPlaceSlab<T>
+where
+    T: Clone,
+{
+    fn clone(&self) -> Self {
+        // Values are cloned before any key is occupied, so a panicking
+        // `T::clone` leaks the clones made so far instead of dropping
+        // uninitialized slots
+        let mut values = Column::with_capacity(self.values.capacity());
+        for (key, value) in self.iter() {
+            values.slot(key).write(value.clone());
+        }
+        Self {
+            keys: self.keys.clone(),
+            values,
+        }
+    }
+}
+
+impl<T> ops::Index<usize> for InPlaceSlab<T> {
+    type Output = T;
+
+    #[cfg_attr(not(slab_no_track_caller), track_caller)]
+    fn index(&self, key: usize) -> &T {
+        self.get(key).expect("invalid key")
+    }
+}
+
+impl<T> ops::IndexMut<usize> for InPlaceSlab<T> {
+    #[cfg_attr(not(slab_no_track_caller), track_caller)]
+    fn index_mut(&mut self, key: usize) -> &mut T {
+        self.get_mut(key).expect("invalid key")
+    }
+}
+
+impl<'a, T> IntoIterator for &'a InPlaceSlab<T> {
+    type Item = (usize, &'a T);
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut InPlaceSlab<T> {
+    type Item = (usize, &'a mut T);
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> IterMut<'a, T> {
+        self.iter_mut()
+    }
+}
+
+impl<T> fmt::Debug for InPlaceSlab<T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if fmt.alternate() {
+            fmt.debug_map().entries(self.iter()).finish()
+        } else {
+            fmt.debug_struct("InPlaceSlab")
+                .field("len", &self.len())
+                .field("cap", &self.capacity())
+                .finish()
+        }
+    }
+}
@@ -0,0 +1,324 @@
+//! A value interner backed by a slab.
+//!
+//! [`Interner`] stores every distinct value once in a [`Slab`] and keeps a
+//! hash index into it, so the key of a value can be found from the value and
+//! the value from its key. Interning the same value again returns the same
+//! key.
+//!
+//! Every call to [`Interner::intern`] also counts a reference to the value.
+//! Releasing the last reference with [`Interner::release`] removes the value
+//! and returns its key to the vacant list. Interners that never release
+//! values can ignore the counts.
+//!
+//! # Examples
+//!
+//! ```
+//! use slab::intern::Interner;
+//!
+//! let mut names = Interner::new();
+//! let a = names.intern("alice".to_string());
+//! let b = names.intern("bob".to_string());
+//! assert_eq!(names.intern("alice".to_string()), a);
+//!
+//! assert_eq!(names.resolve(b), "bob");
+//! assert_eq!(names.get("alice"), Some(a));
+//!
+//! // "alice" was interned twice
+//! assert_eq!(names.release(a), None);
+//! assert_eq!(names.release(a).as_deref(), Some("alice"));
+//! assert_eq!(names.get("alice"), None);
+//! ```
+
+use std::borrow::Borrow;
+use std::collections::hash_map::{self, HashMap, RandomState};
+use std::hash::{BuildHasher, Hash, Hasher};
+use std::{fmt, ops};
+
+use crate::list::{ClearableListStorage, ListStorage, MutRefListStorage, VecStorage};
+use crate::{Iter, Slab};
+
+/// Stores distinct values under stable keys.
+///
+/// See the [module documentation] for more details.
+///
+/// [module documentation]: index.html
+pub struct Interner<T, L: ListStorage = VecStorage, S = RandomState> {
+    values: Slab<T, L>,
+
+    // Reference count of every key, which is 0 for vacant keys
+    refs: Vec<usize>,
+
+    // Keys of the values with each hash
+    index: HashMap<u64, Bucket>,
+    hash_builder: S,
+}
+
+enum Bucket {
+    One(usize),
+    Many(Vec<usize>),
+}
+
+impl Bucket {
+    fn keys(&self) -> &[usize] {
+        match self {
+            Bucket::One(key) => std::slice::from_ref(key),
+            Bucket::Many(keys) => keys,
+        }
+    }
+}
+
+impl<T> Interner<T> {
+    /// Construct a new, empty `Interner`.
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    /// Construct a new, empty `Interner` with space for `capacity` values.
+    pub fn with_capacity(capacity: usize) -> Self {
+        let mut interner = Self::with_slab(Slab::with_capacity(capacity), RandomState::new());
+        interner.refs.reserve(capacity);
+        interner.index.reserve(capacity);
+        interner
+    }
+}
+
+impl<T, S> Interner<T, VecStorage, S> {
+    /// Construct a new, empty `Interner` that hashes values with
+    /// `hash_builder`.
+    pub fn with_hasher(hash_builder: S) -> Self {
+        Self::with_slab(Slab::new(), hash_builder)
+    }
+
+    /// Return an iterator over the keys and values in key order.
+    pub fn iter(&self) -> Iter<'_, T> {
+        self.values.iter()
+    }
+}
+
+impl<T, L: ListStorage, S> Interner<T, L, S> {
+    /// Construct a new `Interner` that stores its values in `slab` and hashes
+    /// them with `hash_builder`.
+    ///
+    /// This allows using a different [`ListStorage`] for the values, or
+    /// reserving capacity up front.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `slab` is not empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use slab::intern::Interner;
+    /// use slab::Slab;
+    /// use std::collections::hash_map::RandomState;
+    ///
+    /// let mut interner = Interner::with_slab(Slab::with_capacity(64), RandomState::new());
+    /// let key = interner.intern(42);
+    /// assert_eq!(interner[key], 42);
+    /// ```
+    pub fn with_slab(slab: Slab<T, L>, hash_builder: S) -> Self {
+        assert!(slab.is_empty(), "an interner needs an empty slab");
+        Self {
+            values: slab,
+            refs: Vec::new(),
+            index: HashMap::new(),
+            hash_builder,
+        }
+    }
+
+    /// Return the number of distinct values.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Return `true` if no values are interned.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Return `true` if a value is associated with the given key.
+    pub fn contains(&self, key: usize) -> bool {
+        self.values.get(key).is_some()
+    }
+
+    /// Return a reference to the value associated with the given key.
+    ///
+    /// If the given key is not associated with a value, then `None` is
+    /// returned.
+    pub fn try_resolve(&self, key: usize) -> Option<&T> {
+        self.values.get(key)
+    }
+
+    /// Return a reference to the value associated with the given key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `key` is not associated with a value.
+    #[cfg_attr(not(slab_no_track_caller), track_caller)]
+    pub fn resolve(&self, key: usize) -> &T {
+        self.try_resolve(key).expect("invalid key")
+    }
+
+    /// Return the number of references to the value associated with the
+    /// given key, or 0 if there is no such value.
+    pub fn ref_count(&self, key: usize) -> usize {
+        self.refs.get(key).copied().unwrap_or(0)
+    }
+
+    /// Remove all values, regardless of their reference counts.
+    pub fn clear(&mut self)
+    where
+        L: ClearableListStorage,
+    {
+        self.values.clear();
+        self.refs.clear();
+        self.index.clear();
+    }
+}
+
+impl<T, L, S> Interner<T, L, S>
+where
+    T: Hash + Eq,
+    L: ListStorage,
+    S: BuildHasher,
+{
+    fn hash<Q>(&self, value: &Q) -> u64
+    where
+        Q: Hash + ?Sized,
+    {
+        let mut hasher = self.hash_builder.build_hasher();
+        value.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    fn find<Q>(&self, hash: u64, value: &Q) -> Option<usize>
+    where
+        T: Borrow<Q>,
+        Q: Eq + ?Sized,
+    {
+        let bucket = self.index.get(&hash)?;
+        bucket
+            .keys()
+            .iter()
+            .copied()
+            .find(|&key| self.values.get(key).map(Borrow::borrow) == Some(value))
+    }
+
+    /// Return the key of a value, interning it first if it is new.
+    ///
+    /// This counts a reference to the value. If an equal value is already
+    /// interned, `value` is dropped.
+    pub fn intern(&mut self, value: T) -> usize {
+        let hash = self.hash(&value);
+        let key = match self.find(hash, &value) {
+            Some(key) => key,
+            None => {
+                let key = self.values.insert(value);
+                match self.index.entry(hash) {
+                    hash_map::Entry::Vacant(entry) => {
+                        entry.insert(Bucket::One(key));
+                    }
+                    hash_map::Entry::Occupied(mut entry) => {
+                        let bucket = entry.get_mut();
+                        match bucket {
+                            Bucket::One(other) => *bucket = Bucket::Many(vec![*other, key]),
+                            Bucket::Many(keys) => keys.push(key),
+                        }
+                    }
+                }
+                if key >= self.refs.len() {
+                    self.refs.resize(key + 1, 0);
+                }
+                key
+            }
+        };
+        self.refs[key] += 1;
+        key
+    }
+
+    /// Return the key of a value if it is interned, without counting a
+    /// reference.
+    pub fn get<Q>(&self, value: &Q) -> Option<usize>
+    where
+        T: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.find(self.hash(value), value)
+    }
+
+    /// Release a reference to the value associated with the given key.
+    ///
+    /// When the last reference is released, the value is removed and
+    /// returned, and its key may be reused by values interned later.
+    /// Otherwise `None` is returned.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `key` is not associated with a value.
+    #[cfg_attr(not(slab_no_track_caller), track_caller)]
+    pub fn release(&mut self, key: usize) -> Option<T>
+    where
+        L: MutRefListStorage,
+    {
+        let refs = match self.refs.get_mut(key) {
+            Some(refs) if *refs > 0 => refs,
+            _ => panic!("invalid key"),
+        };
+        *refs -= 1;
+        if *refs > 0 {
+            return None;
+        }
+
+        let value = self.values.try_remove(key).expect("invalid key");
+        let hash = self.hash(&value);
+        if let hash_map::Entry::Occupied(mut entry) = self.index.entry(hash) {
+            let bucket = entry.get_mut();
+            match bucket {
+                Bucket::One(_) => {
+                    entry.remove();
+                }
+                Bucket::Many(keys) => {
+                    keys.retain(|&other| other != key);
+                    if let [other] = keys[..] {
+                        *bucket = Bucket::One(other);
+                    }
+                }
+            }
+        }
+        Some(value)
+    }
+}
+
+impl<T> Default for Interner<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, L: ListStorage, S> ops::Index<usize> for Interner<T, L, S> {
+    type Output = T;
+
+    #[cfg_attr(not(slab_no_track_caller), track_caller)]
+    fn index(&self, key: usize) -> &T {
+        self.resolve(key)
+    }
+}
+
+impl<'a, T, S> IntoIterator for &'a Interner<T, VecStorage, S> {
+    type Item = (usize, &'a T);
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<T, L: ListStorage, S> fmt::Debug for Interner<T, L, S> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("Interner")
+            .field("len", &self.len())
+            .field("buckets", &self.index.len())
+            .finish()
+    }
+}
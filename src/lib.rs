@@ -113,9 +113,32 @@ extern crate std as alloc;
 #[cfg(feature = "serde")]
 mod serde;
 
+pub mod blob;
 mod builder;
+pub mod dense;
+mod error;
+pub mod group;
+pub mod heap;
+pub mod inplace;
+pub mod linked;
 pub mod list;
+pub mod niche;
+pub mod ordered;
+pub mod pool;
+pub mod rc;
+pub mod soa;
 
+#[cfg(feature = "std")]
+pub mod intern;
+#[cfg(feature = "std")]
+pub mod lru;
+#[cfg(feature = "std")]
+pub mod shard;
+#[cfg(target_has_atomic = "ptr")]
+pub mod sync;
+
+#[cfg(feature = "timer")]
+pub mod timer;
 #[cfg(feature = "tx")]
 pub mod tx;
 
@@ -127,6 +150,13 @@ use list::{
     VecStorage,
 };
 
+#[cfg(feature = "serde")]
+pub use crate::serde::{repr, SlabSeed};
+pub use builder::{DuplicateKeyError, DuplicateKeys, SlabBuilder};
+pub use error::SlabError;
+#[cfg(feature = "derive")]
+pub use slab_derive::SlabSoA;
+
 /// Pre-allocated storage for a uniform data type
 ///
 /// See the [module documentation] for more details.
@@ -198,6 +228,16 @@ pub struct VacantEntry<'a, T> {
     key: usize,
 }
 
+/// A handle to a run of consecutive vacant entries in a `Slab`.
+///
+/// It is returned by [`Slab::reserve_contiguous`] and allows constructing
+/// values with the keys that they will be assigned to.
+#[derive(Debug)]
+pub struct VacantRun<'a, T> {
+    slab: &'a mut Slab<T>,
+    keys: ops::Range<usize>,
+}
+
 /// A consuming iterator over the values stored in a `Slab`
 pub struct IntoIter<T> {
     entries: iter::Enumerate<vec::IntoIter<Entry<T>>>,
@@ -346,6 +386,56 @@ impl<T, L: ListStorage> Slab<T, L> {
         }
     }
 
+    /// Return a reference to the value associated with the given key, or an
+    /// error telling why there is none.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use slab::*;
+    /// let mut slab = Slab::new();
+    /// let key = slab.insert("hello");
+    ///
+    /// assert_eq!(slab.try_get(key), Ok(&"hello"));
+    /// assert_eq!(slab.try_get(123), Err(SlabError::OutOfBounds(123)));
+    /// ```
+    pub fn try_get(&self, key: usize) -> Result<&T, SlabError> {
+        self.get(key).ok_or_else(|| self.lookup_error(key))
+    }
+
+    /// Return a mutable reference to the value associated with the given
+    /// key, or an error telling why there is none.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use slab::*;
+    /// let mut slab = Slab::new();
+    /// let key = slab.insert("hello");
+    ///
+    /// *slab.try_get_mut(key).unwrap() = "world";
+    /// assert_eq!(slab[key], "world");
+    ///
+    /// slab.remove(key);
+    /// assert_eq!(slab.try_get_mut(key), Err(SlabError::Vacant(key)));
+    /// ```
+    pub fn try_get_mut(&mut self, key: usize) -> Result<&mut T, SlabError>
+    where
+        L: MutRefListStorage,
+    {
+        let error = self.lookup_error(key);
+        self.get_mut(key).ok_or(error)
+    }
+
+    // The reason why `key` is not associated with a value
+    fn lookup_error(&self, key: usize) -> SlabError {
+        if key < self.entries.len() {
+            SlabError::Vacant(key)
+        } else {
+            SlabError::OutOfBounds(key)
+        }
+    }
+
     /// Return two mutable references to the values associated with the two
     /// given keys simultaneously.
     ///
@@ -400,6 +490,43 @@ impl<T, L: ListStorage> Slab<T, L> {
         }
     }
 
+    /// Return two mutable references to the values associated with the two
+    /// given keys simultaneously, or an error telling why that is not
+    /// possible.
+    ///
+    /// Unlike [`get2_mut`](Slab::get2_mut), this does not panic if the keys
+    /// are the same.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use slab::*;
+    /// let mut slab = Slab::new();
+    /// let key1 = slab.insert(1);
+    /// let key2 = slab.insert(2);
+    ///
+    /// let (value1, value2) = slab.try_get2_mut(key1, key2).unwrap();
+    /// std::mem::swap(value1, value2);
+    /// assert_eq!(slab[key1], 2);
+    ///
+    /// assert_eq!(slab.try_get2_mut(key1, key1), Err(SlabError::SameKey(key1)));
+    /// assert_eq!(slab.try_get2_mut(key1, 9), Err(SlabError::OutOfBounds(9)));
+    /// ```
+    pub fn try_get2_mut(&mut self, key1: usize, key2: usize) -> Result<(&mut T, &mut T), SlabError>
+    where
+        L: AsSliceListStorage,
+    {
+        if key1 == key2 {
+            return Err(SlabError::SameKey(key1));
+        }
+        self.try_get(key1)?;
+        self.try_get(key2)?;
+        match self.get2_mut(key1, key2) {
+            Some(values) => Ok(values),
+            None => unreachable!(),
+        }
+    }
+
     /// Return a reference to the value associated with the given key without
     /// performing bounds checking.
     ///
@@ -547,6 +674,30 @@ impl<T, L: ListStorage> Slab<T, L> {
     /// ```
     #[cfg_attr(not(slab_no_track_caller), track_caller)]
     pub fn key_of(&self, present_element: &T) -> usize
+    where
+        L: AsSliceListStorage,
+    {
+        match self.try_key_of(present_element) {
+            Ok(key) => key,
+            Err(_) => panic!("The reference points to a value outside this slab"),
+        }
+    }
+
+    /// Get the key for an element in the slab, or
+    /// [`SlabError::ForeignReference`] if the reference does not point to an
+    /// element of the slab.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use slab::*;
+    /// let mut slab = Slab::new();
+    /// let key = slab.insert(0);
+    ///
+    /// assert_eq!(slab.try_key_of(&slab[key]), Ok(key));
+    /// assert_eq!(slab.try_key_of(&0), Err(SlabError::ForeignReference));
+    /// ```
+    pub fn try_key_of(&self, present_element: &T) -> Result<usize, SlabError>
     where
         L: AsSliceListStorage,
     {
@@ -559,10 +710,10 @@ impl<T, L: ListStorage> Slab<T, L> {
         let key = byte_offset / mem::size_of::<Entry<T>>();
         // Prevent returning unspecified (but out of bounds) values
         if key >= self.entries.len() {
-            panic!("The reference points to a value outside this slab");
+            return Err(SlabError::ForeignReference);
         }
         // The reference cannot point to a vacant entry, because then it would not be valid
-        key
+        Ok(key)
     }
 
     fn insert_at(&mut self, key: usize, val: T) {
@@ -788,6 +939,34 @@ impl<T> Slab<T> {
         self.entries.reserve(need_add);
     }
 
+    /// Try to reserve capacity for at least `additional` more values, like
+    /// [`reserve`](Slab::reserve) but without panicking.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SlabError::CapacityOverflow`] if the new capacity would
+    /// exceed `isize::MAX` bytes or the allocation fails.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use slab::*;
+    /// let mut slab: Slab<u64> = Slab::new();
+    /// slab.try_reserve(10).unwrap();
+    /// assert!(slab.capacity() >= 10);
+    ///
+    /// assert_eq!(slab.try_reserve(usize::MAX), Err(SlabError::CapacityOverflow));
+    /// ```
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), SlabError> {
+        if self.capacity() - self.len >= additional {
+            return Ok(());
+        }
+        let need_add = additional - (self.entries.len() - self.len);
+        self.entries
+            .try_reserve(need_add)
+            .map_err(|_| SlabError::CapacityOverflow)
+    }
+
     /// Reserve the minimum capacity required to store exactly `additional`
     /// more values.
     ///
@@ -1104,6 +1283,136 @@ impl<T> Slab<T> {
         }
     }
 
+    /// Insert all values yielded by an iterator under consecutive keys,
+    /// returning the range of keys assigned to them.
+    ///
+    /// The values are stored in the lowest run of vacant entries that is long
+    /// enough, or after the last occupied entry if there is none. The other
+    /// vacant entries keep their order in the vacant list. Finding the run
+    /// takes time proportional to the number of entries.
+    ///
+    /// If the iterator panics, the slab is left unchanged. Use
+    /// [`reserve_contiguous`](Slab::reserve_contiguous) to learn the keys
+    /// before creating the values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use slab::*;
+    /// let mut slab = Slab::new();
+    /// for i in 0..6 {
+    ///     slab.insert(i);
+    /// }
+    /// slab.remove(1);
+    /// slab.remove(3);
+    /// slab.remove(4);
+    ///
+    /// // Only keys 3 and 4 are next to each other
+    /// assert_eq!(slab.insert_contiguous(vec![10, 11]), 3..5);
+    /// assert_eq!(slab.insert_contiguous(vec![20, 21]), 6..8);
+    /// assert_eq!(slab.insert(30), 1);
+    /// ```
+    pub fn insert_contiguous<I>(&mut self, values: I) -> ops::Range<usize>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        // Collect first, so that the iterator can't fail halfway through
+        let values: Vec<T> = values.into_iter().collect();
+        let run = self.reserve_contiguous(values.len());
+        run.slab.fill_run(run.keys, values)
+    }
+
+    /// Return a handle to the run of `n` vacant keys that `insert_contiguous`
+    /// would use, reserving the capacity needed to fill it.
+    ///
+    /// The handle borrows the slab, so the keys stay vacant for it until it
+    /// is filled with [`VacantRun::insert`]. Dropping it leaves the slab
+    /// unchanged.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the run would end past `usize::MAX` or the new capacity
+    /// exceeds `isize::MAX` bytes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use slab::*;
+    /// let mut slab = Slab::new();
+    /// slab.insert((0, 'a'));
+    ///
+    /// let run = slab.reserve_contiguous(3);
+    /// let keys = run.keys();
+    /// assert_eq!(keys, 1..4);
+    /// run.insert(keys.clone().zip("bcd".chars()));
+    ///
+    /// assert_eq!(slab[2], (2, 'c'));
+    /// assert!(slab.capacity() >= 4);
+    /// ```
+    #[cfg_attr(not(slab_no_track_caller), track_caller)]
+    pub fn reserve_contiguous(&mut self, n: usize) -> VacantRun<'_, T> {
+        let keys = self.find_vacant_run(n);
+        let additional = keys.end.saturating_sub(self.entries.len());
+        self.entries.reserve(additional);
+        VacantRun { slab: self, keys }
+    }
+
+    // Store one value for every key of a vacant run, which has enough
+    // capacity reserved.
+    fn fill_run(&mut self, range: ops::Range<usize>, values: Vec<T>) -> ops::Range<usize> {
+        // Unlink the vacant entries in the range, and find the last one that
+        // remains in the list
+        let mut last = None;
+        let mut key = self.next;
+        while key < self.entries.len() {
+            let next = match self.entries[key] {
+                Entry::Vacant(next) => next,
+                Entry::Occupied(_) => unreachable!(),
+            };
+            if !range.contains(&key) {
+                last = Some(key);
+            } else if let Some(last) = last {
+                self.entries[last] = Entry::Vacant(next);
+            } else {
+                self.next = next;
+            }
+            key = next;
+        }
+
+        for (key, value) in range.clone().zip(values) {
+            if key < self.entries.len() {
+                self.entries[key] = Entry::Occupied(value);
+            } else {
+                self.entries.push(Entry::Occupied(value));
+            }
+        }
+        self.len += range.len();
+
+        // The vacant list ends with the number of entries, which may have
+        // grown
+        let end = self.entries.len();
+        match last {
+            Some(last) => self.entries[last] = Entry::Vacant(end),
+            None => self.next = end,
+        }
+        range
+    }
+
+    // Find the lowest run of `n` vacant keys, which may extend past the end
+    // of the entries.
+    #[cfg_attr(not(slab_no_track_caller), track_caller)]
+    fn find_vacant_run(&self, n: usize) -> ops::Range<usize> {
+        let mut start = 0;
+        for (key, entry) in self.entries.iter().enumerate() {
+            match entry {
+                Entry::Vacant(_) if key + 1 - start >= n => break,
+                Entry::Vacant(_) => {}
+                Entry::Occupied(_) => start = key + 1,
+            }
+        }
+        start..start.checked_add(n).expect("capacity overflow")
+    }
+
     /// Remove and return the value associated with the given key.
     ///
     /// The key is then released and may be associated with future stored
@@ -1129,6 +1438,73 @@ impl<T> Slab<T> {
         self.try_remove(key).expect("invalid key")
     }
 
+    /// Remove and return the value associated with the given key, or an
+    /// error telling why there is none.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use slab::*;
+    /// let mut slab = Slab::new();
+    /// let hello = slab.insert("hello");
+    ///
+    /// assert_eq!(slab.remove_checked(hello), Ok("hello"));
+    /// assert_eq!(slab.remove_checked(hello), Err(SlabError::Vacant(hello)));
+    /// ```
+    pub fn remove_checked(&mut self, key: usize) -> Result<T, SlabError> {
+        self.try_remove(key).ok_or_else(|| self.lookup_error(key))
+    }
+
+    /// Remove the values associated with all given keys, returning them in
+    /// the same order as the keys.
+    ///
+    /// All keys are checked before anything is removed, so if any of them is
+    /// not associated with a value or appears more than once, an error is
+    /// returned and the slab is left untouched. The removed keys are put at
+    /// the front of the vacant list in ascending order, so the lowest of them
+    /// is reused first.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use slab::*;
+    /// let mut slab = Slab::new();
+    /// let keys: Vec<_> = (0..5).map(|i| slab.insert(i * 10)).collect();
+    ///
+    /// assert_eq!(slab.remove_many(&[keys[3], 9]), Err(SlabError::OutOfBounds(9)));
+    /// assert_eq!(slab.len(), 5);
+    ///
+    /// assert_eq!(slab.remove_many(&[keys[3], keys[1]]), Ok(vec![30, 10]));
+    /// assert_eq!(slab.insert(50), keys[1]);
+    /// assert_eq!(slab.insert(60), keys[3]);
+    /// ```
+    pub fn remove_many(&mut self, keys: &[usize]) -> Result<Vec<T>, SlabError> {
+        for &key in keys {
+            self.try_get(key)?;
+        }
+        let mut sorted = keys.to_vec();
+        sorted.sort_unstable();
+        if let Some(pair) = sorted.windows(2).find(|pair| pair[0] == pair[1]) {
+            return Err(SlabError::SameKey(pair[0]));
+        }
+
+        let mut values = Vec::with_capacity(keys.len());
+        for &key in keys {
+            match mem::replace(&mut self.entries[key], Entry::Vacant(0)) {
+                Entry::Occupied(value) => values.push(value),
+                Entry::Vacant(_) => unreachable!(),
+            }
+        }
+
+        // Link the removed keys in front of the vacant list, lowest first
+        for &key in sorted.iter().rev() {
+            self.entries[key] = Entry::Vacant(self.next);
+            self.next = key;
+        }
+        self.len -= keys.len();
+        Ok(values)
+    }
+
     /// Return `true` if a value is associated with the given key.
     ///
     /// # Examples
@@ -1188,6 +1564,121 @@ impl<T> Slab<T> {
         }
     }
 
+    /// Convert the slab into a slab of other values, keeping the layout.
+    ///
+    /// `f` is called with the key and value of every element in key order.
+    /// The new values keep the keys, and the new slab has the same capacity
+    /// and vacant list, so it hands out keys in the same order as the old
+    /// one would have.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use slab::*;
+    /// let mut slab = Slab::new();
+    /// let a = slab.insert(1);
+    /// let b = slab.insert(2);
+    /// slab.remove(a);
+    ///
+    /// let mut slab = slab.map(|key, value| format!("{}: {}", key, value));
+    /// assert_eq!(slab[b], "1: 2");
+    /// assert_eq!(slab.insert(String::new()), a);
+    /// ```
+    pub fn map<U, F>(self, mut f: F) -> Slab<U>
+    where
+        F: FnMut(usize, T) -> U,
+    {
+        match self.try_map(|key, value| Ok::<U, core::convert::Infallible>(f(key, value))) {
+            Ok(slab) => slab,
+            Err(infallible) => match infallible {},
+        }
+    }
+
+    /// Convert the slab into a slab of other values, keeping the layout, or
+    /// return the first error returned by `f`.
+    ///
+    /// Like [`map`](Slab::map), but `f` can fail. The remaining values are
+    /// dropped when it does.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use slab::*;
+    /// let mut slab = Slab::new();
+    /// slab.insert("1");
+    /// let key = slab.insert("2");
+    ///
+    /// let numbers = slab.clone().try_map(|_, value| value.parse::<u32>());
+    /// assert_eq!(numbers.unwrap()[key], 2);
+    ///
+    /// slab.insert("three");
+    /// assert!(slab.try_map(|_, value| value.parse::<u32>()).is_err());
+    /// ```
+    pub fn try_map<U, E, F>(self, mut f: F) -> Result<Slab<U>, E>
+    where
+        F: FnMut(usize, T) -> Result<U, E>,
+    {
+        let mut entries = Vec::with_capacity(self.entries.capacity());
+        for (key, entry) in self.entries.into_iter().enumerate() {
+            entries.push(match entry {
+                Entry::Vacant(next) => Entry::Vacant(next),
+                Entry::Occupied(value) => Entry::Occupied(f(key, value)?),
+            });
+        }
+        Ok(Slab {
+            entries,
+            len: self.len,
+            next: self.next,
+        })
+    }
+
+    /// Convert the slab into a slab of other values, dropping the values for
+    /// which `f` returns `None`, and keeping the layout otherwise.
+    ///
+    /// The keys of the dropped values become vacant as if they were removed
+    /// in ascending order, like [`retain`](Slab::retain) does, so the highest
+    /// of them is reused first.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use slab::*;
+    /// let mut slab = Slab::new();
+    /// for i in 0..4 {
+    ///     slab.insert(i);
+    /// }
+    ///
+    /// let mut odd = slab.filter_map(|_, value| Some(value * 10).filter(|_| value % 2 == 1));
+    /// assert_eq!(odd.len(), 2);
+    /// assert_eq!(odd[3], 30);
+    /// assert_eq!(odd.insert(0), 2);
+    /// assert_eq!(odd.insert(0), 0);
+    /// ```
+    pub fn filter_map<U, F>(self, mut f: F) -> Slab<U>
+    where
+        F: FnMut(usize, T) -> Option<U>,
+    {
+        let mut slab: Slab<U> = Slab {
+            entries: Vec::with_capacity(self.entries.capacity()),
+            len: self.len,
+            next: self.next,
+        };
+        for (key, entry) in self.entries.into_iter().enumerate() {
+            let entry = match entry {
+                Entry::Vacant(next) => Entry::Vacant(next),
+                Entry::Occupied(value) => match f(key, value) {
+                    Some(value) => Entry::Occupied(value),
+                    None => {
+                        slab.len -= 1;
+                        Entry::Vacant(mem::replace(&mut slab.next, key))
+                    }
+                },
+            };
+            slab.entries.push(entry);
+        }
+        slab
+    }
+
     /// Return a draining iterator that removes all elements from the slab and
     /// yields the removed items.
     ///
@@ -1314,7 +1805,7 @@ impl<T> FromIterator<(usize, T)> for Slab<T> {
         I: IntoIterator<Item = (usize, T)>,
     {
         let iterator = iterable.into_iter();
-        let mut builder = builder::Builder::with_capacity(iterator.size_hint().0);
+        let mut builder = SlabBuilder::with_capacity(iterator.size_hint().0);
 
         for (key, value) in iterator {
             builder.pair(key, value)
@@ -1438,6 +1929,50 @@ impl<'a, T> VacantEntry<'a, T> {
     }
 }
 
+// ===== VacantRun =====
+
+impl<'a, T> VacantRun<'a, T> {
+    /// Store the values under the keys of the run, in order, returning the
+    /// range of keys.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of values differs from the number of keys. The
+    /// slab is left unchanged in that case, and if the iterator panics.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use slab::*;
+    /// let mut slab = Slab::new();
+    ///
+    /// let run = slab.reserve_contiguous(2);
+    /// assert_eq!(run.insert(vec!["a", "b"]), 0..2);
+    /// assert_eq!(slab[1], "b");
+    /// ```
+    #[cfg_attr(not(slab_no_track_caller), track_caller)]
+    pub fn insert<I>(self, values: I) -> ops::Range<usize>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let values: Vec<T> = values.into_iter().collect();
+        assert_eq!(
+            values.len(),
+            self.keys.len(),
+            "wrong number of values for the vacant run"
+        );
+        self.slab.fill_run(self.keys, values)
+    }
+
+    /// Return the keys of the run.
+    ///
+    /// Values stored with [`insert`](VacantRun::insert) will be associated
+    /// with these keys.
+    pub fn keys(&self) -> ops::Range<usize> {
+        self.keys.clone()
+    }
+}
+
 // ===== IntoIter =====
 
 impl<T> Iterator for IntoIter<T> {
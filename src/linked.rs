@@ -0,0 +1,444 @@
+//! A doubly linked list whose nodes are stored in a slab.
+//!
+//! Nodes are identified by their slab key, which stays the same for as long
+//! as the node is in the list. This makes it possible to remove a node or move
+//! it to either end in O(1) time given only its key, which is what LRU caches
+//! and scheduler queues need.
+//!
+//! # Examples
+//!
+//! ```
+//! use slab::linked::SlabList;
+//!
+//! let mut list = SlabList::new();
+//! let a = list.push_back('a');
+//! let b = list.push_back('b');
+//! let c = list.push_back('c');
+//!
+//! list.move_to_front(c);
+//! assert_eq!(list.remove(b), 'b');
+//!
+//! let order: Vec<_> = list.iter().map(|(_, &value)| value).collect();
+//! assert_eq!(order, ['c', 'a']);
+//! assert_eq!(list.front_key(), Some(c));
+//! assert_eq!(list.back_key(), Some(a));
+//! ```
+
+use core::iter::FusedIterator;
+use core::{fmt, ops};
+
+use crate::Slab;
+
+/// A doubly linked list with stable keys.
+///
+/// See the [module documentation] for more details.
+///
+/// [module documentation]: index.html
+pub struct SlabList<T> {
+    nodes: Slab<Node<T>>,
+    head: Option<usize>,
+    tail: Option<usize>,
+}
+
+pub(crate) struct Node<T> {
+    pub(crate) value: T,
+    pub(crate) prev: Option<usize>,
+    pub(crate) next: Option<usize>,
+}
+
+/// An iterator over the values of a [`SlabList`] in list order.
+pub struct Iter<'a, T> {
+    list: &'a SlabList<T>,
+    front: Option<usize>,
+    back: Option<usize>,
+    len: usize,
+}
+
+impl<T> SlabList<T> {
+    /// Construct a new, empty `SlabList`.
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    /// Construct a new, empty `SlabList` with the specified capacity.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            nodes: Slab::with_capacity(capacity),
+            head: None,
+            tail: None,
+        }
+    }
+
+    // The nodes must form a single list from `head` to `tail`.
+    #[cfg(feature = "serde")]
+    pub(crate) fn from_parts(
+        nodes: Slab<Node<T>>,
+        head: Option<usize>,
+        tail: Option<usize>,
+    ) -> Self {
+        Self { nodes, head, tail }
+    }
+
+    /// Return the number of values the list can store without reallocating.
+    pub fn capacity(&self) -> usize {
+        self.nodes.capacity()
+    }
+
+    /// Return the number of values in the list.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Return `true` if the list is empty.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Remove all values from the list.
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+        self.head = None;
+        self.tail = None;
+    }
+
+    /// Return `true` if a value is associated with the given key.
+    pub fn contains(&self, key: usize) -> bool {
+        self.nodes.contains(key)
+    }
+
+    /// Return a reference to the value associated with the given key.
+    ///
+    /// If the given key is not associated with a value, then `None` is
+    /// returned.
+    pub fn get(&self, key: usize) -> Option<&T> {
+        self.nodes.get(key).map(|node| &node.value)
+    }
+
+    /// Return a mutable reference to the value associated with the given key.
+    ///
+    /// If the given key is not associated with a value, then `None` is
+    /// returned.
+    pub fn get_mut(&mut self, key: usize) -> Option<&mut T> {
+        self.nodes.get_mut(key).map(|node| &mut node.value)
+    }
+
+    /// Return the key of the first value in the list.
+    pub fn front_key(&self) -> Option<usize> {
+        self.head
+    }
+
+    /// Return the key of the last value in the list.
+    pub fn back_key(&self) -> Option<usize> {
+        self.tail
+    }
+
+    /// Return a reference to the first value in the list.
+    pub fn front(&self) -> Option<&T> {
+        self.get(self.head?)
+    }
+
+    /// Return a reference to the last value in the list.
+    pub fn back(&self) -> Option<&T> {
+        self.get(self.tail?)
+    }
+
+    /// Return the key of the value following the one associated with `key`.
+    ///
+    /// Returns `None` if `key` is the last key or not associated with a value.
+    pub fn next_key(&self, key: usize) -> Option<usize> {
+        self.nodes.get(key)?.next
+    }
+
+    /// Return the key of the value preceding the one associated with `key`.
+    ///
+    /// Returns `None` if `key` is the first key or not associated with a
+    /// value.
+    pub fn prev_key(&self, key: usize) -> Option<usize> {
+        self.nodes.get(key)?.prev
+    }
+
+    /// Insert a value at the front of the list, returning its key.
+    pub fn push_front(&mut self, value: T) -> usize {
+        let key = self.nodes.insert(Node {
+            value,
+            prev: None,
+            next: None,
+        });
+        self.link_front(key);
+        key
+    }
+
+    /// Insert a value at the back of the list, returning its key.
+    pub fn push_back(&mut self, value: T) -> usize {
+        let key = self.nodes.insert(Node {
+            value,
+            prev: None,
+            next: None,
+        });
+        self.link_back(key);
+        key
+    }
+
+    /// Insert a value right after the one associated with `key`, returning
+    /// the key of the new value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `key` is not associated with a value.
+    #[cfg_attr(not(slab_no_track_caller), track_caller)]
+    pub fn insert_after(&mut self, key: usize, value: T) -> usize {
+        let next = self.nodes.get(key).expect("invalid key").next;
+        let new_key = self.nodes.insert(Node {
+            value,
+            prev: Some(key),
+            next,
+        });
+        self.nodes[key].next = Some(new_key);
+        match next {
+            Some(next) => self.nodes[next].prev = Some(new_key),
+            None => self.tail = Some(new_key),
+        }
+        new_key
+    }
+
+    /// Insert a value right before the one associated with `key`, returning
+    /// the key of the new value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `key` is not associated with a value.
+    #[cfg_attr(not(slab_no_track_caller), track_caller)]
+    pub fn insert_before(&mut self, key: usize, value: T) -> usize {
+        let prev = self.nodes.get(key).expect("invalid key").prev;
+        let new_key = self.nodes.insert(Node {
+            value,
+            prev,
+            next: Some(key),
+        });
+        self.nodes[key].prev = Some(new_key);
+        match prev {
+            Some(prev) => self.nodes[prev].next = Some(new_key),
+            None => self.head = Some(new_key),
+        }
+        new_key
+    }
+
+    /// Remove and return the first value in the list.
+    pub fn pop_front(&mut self) -> Option<T> {
+        self.try_remove(self.head?)
+    }
+
+    /// Remove and return the last value in the list.
+    pub fn pop_back(&mut self) -> Option<T> {
+        self.try_remove(self.tail?)
+    }
+
+    /// Remove and return the value associated with the given key.
+    ///
+    /// If the given key is not associated with a value, then `None` is
+    /// returned.
+    pub fn try_remove(&mut self, key: usize) -> Option<T> {
+        if !self.nodes.contains(key) {
+            return None;
+        }
+        self.unlink(key);
+        Some(self.nodes.remove(key).value)
+    }
+
+    /// Remove and return the value associated with the given key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `key` is not associated with a value.
+    #[cfg_attr(not(slab_no_track_caller), track_caller)]
+    pub fn remove(&mut self, key: usize) -> T {
+        self.try_remove(key).expect("invalid key")
+    }
+
+    /// Move the value associated with the given key to the front of the list.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `key` is not associated with a value.
+    #[cfg_attr(not(slab_no_track_caller), track_caller)]
+    pub fn move_to_front(&mut self, key: usize) {
+        assert!(self.nodes.contains(key), "invalid key");
+        if self.head != Some(key) {
+            self.unlink(key);
+            self.link_front(key);
+        }
+    }
+
+    /// Move the value associated with the given key to the back of the list.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `key` is not associated with a value.
+    #[cfg_attr(not(slab_no_track_caller), track_caller)]
+    pub fn move_to_back(&mut self, key: usize) {
+        assert!(self.nodes.contains(key), "invalid key");
+        if self.tail != Some(key) {
+            self.unlink(key);
+            self.link_back(key);
+        }
+    }
+
+    /// Return an iterator over the keys and values in list order.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            list: self,
+            front: self.head,
+            back: self.tail,
+            len: self.len(),
+        }
+    }
+
+    // Link an unlinked node in as the new head.
+    fn link_front(&mut self, key: usize) {
+        let node = &mut self.nodes[key];
+        node.prev = None;
+        node.next = self.head;
+        match self.head {
+            Some(head) => self.nodes[head].prev = Some(key),
+            None => self.tail = Some(key),
+        }
+        self.head = Some(key);
+    }
+
+    // Link an unlinked node in as the new tail.
+    fn link_back(&mut self, key: usize) {
+        let node = &mut self.nodes[key];
+        node.next = None;
+        node.prev = self.tail;
+        match self.tail {
+            Some(tail) => self.nodes[tail].next = Some(key),
+            None => self.head = Some(key),
+        }
+        self.tail = Some(key);
+    }
+
+    // Connect the neighbours of a node to each other. The node's own links
+    // are left as they are.
+    fn unlink(&mut self, key: usize) {
+        let Node { prev, next, .. } = self.nodes[key];
+        match prev {
+            Some(prev) => self.nodes[prev].next = next,
+            None => self.head = next,
+        }
+        match next {
+            Some(next) => self.nodes[next].prev = prev,
+            None => self.tail = prev,
+        }
+    }
+}
+
+impl<T> Default for SlabList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> ops::Index<usize> for SlabList<T> {
+    type Output = T;
+
+    #[cfg_attr(not(slab_no_track_caller), track_caller)]
+    fn index(&self, key: usize) -> &T {
+        self.get(key).expect("invalid key")
+    }
+}
+
+impl<T> ops::IndexMut<usize> for SlabList<T> {
+    #[cfg_attr(not(slab_no_track_caller), track_caller)]
+    fn index_mut(&mut self, key: usize) -> &mut T {
+        self.get_mut(key).expect("invalid key")
+    }
+}
+
+impl<'a, T> IntoIterator for &'a SlabList<T> {
+    type Item = (usize, &'a T);
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<T> fmt::Debug for SlabList<T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if fmt.alternate() {
+            fmt.debug_map().entries(self.iter()).finish()
+        } else {
+            fmt.debug_struct("SlabList")
+                .field("len", &self.len())
+                .field("cap", &self.capacity())
+                .finish()
+        }
+    }
+}
+
+// ===== Iter =====
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = (usize, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        let key = self.front?;
+        let node = &self.list.nodes[key];
+        self.front = node.next;
+        self.len -= 1;
+        Some((key, &node.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for Iter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        let key = self.back?;
+        let node = &self.list.nodes[key];
+        self.back = node.prev;
+        self.len -= 1;
+        Some((key, &node.value))
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+impl<T> FusedIterator for Iter<'_, T> {}
+
+impl<T> Clone for Iter<'_, T> {
+    fn clone(&self) -> Self {
+        Self {
+            list: self.list,
+            front: self.front,
+            back: self.back,
+            len: self.len,
+        }
+    }
+}
+
+impl<T> fmt::Debug for Iter<'_, T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("Iter")
+            .field("remaining", &self.len)
+            .finish()
+    }
+}
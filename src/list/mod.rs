@@ -17,6 +17,12 @@ pub trait ClearableListStorage: ListStorage {
     fn clear<T>(list: &mut Self::List<T>);
 }
 
+pub trait CapacityListStorage: ListStorage {
+    fn with_capacity<T>(capacity: usize) -> Self::List<T>;
+    fn capacity<T>(list: &Self::List<T>) -> usize;
+    fn reserve<T>(list: &mut Self::List<T>, additional: usize);
+}
+
 pub trait AsSliceListStorage: ListStorage {
     fn as_slice<T>(list: &Self::List<T>) -> &[T];
     fn as_mut_slice<T>(list: &mut Self::List<T>) -> &mut [T];
@@ -115,7 +115,10 @@ where
     L::Target: List,
 {
     type Item = <L::Target as List>::Item;
-    type ItemMut<'a> = TxItemMut<'a, Self::Item> where Self: 'a;
+    type ItemMut<'a>
+        = TxItemMut<'a, Self::Item>
+    where
+        Self: 'a;
 
     fn len(&self) -> usize {
         self.base.len() + self.patch.pushed_items.len()
@@ -1,4 +1,7 @@
-use super::{AsSliceListStorage, ClearableListStorage, List, ListStorage, MutRefListStorage};
+use super::{
+    AsSliceListStorage, CapacityListStorage, ClearableListStorage, List, ListStorage,
+    MutRefListStorage,
+};
 use alloc::vec::Vec;
 
 #[derive(Debug)]
@@ -13,6 +16,18 @@ impl ClearableListStorage for VecStorage {
         list.clear()
     }
 }
+impl CapacityListStorage for VecStorage {
+    fn with_capacity<T>(capacity: usize) -> Self::List<T> {
+        Vec::with_capacity(capacity)
+    }
+    fn capacity<T>(list: &Self::List<T>) -> usize {
+        list.capacity()
+    }
+    fn reserve<T>(list: &mut Self::List<T>, additional: usize) {
+        list.reserve(additional)
+    }
+}
+
 impl MutRefListStorage for VecStorage {
     fn into_mut_ref<T>(
         item_mut: <<Self as ListStorage>::List<T> as List>::ItemMut<'_>,
@@ -32,7 +47,10 @@ impl AsSliceListStorage for VecStorage {
 
 impl<T> List for Vec<T> {
     type Item = T;
-    type ItemMut<'a> = &'a mut T where Self: 'a;
+    type ItemMut<'a>
+        = &'a mut T
+    where
+        Self: 'a;
 
     fn len(&self) -> usize {
         Vec::len(self)
@@ -0,0 +1,222 @@
+//! A least recently used cache backed by a slab.
+//!
+//! [`LruSlab`] keeps its entries in a [`SlabList`] ordered from most to least
+//! recently used, and a hash index from keys to slab keys. Once the cache is
+//! full, inserting a new entry evicts the least recently used one, whose slab
+//! key is then reused for the new entry through the vacant list.
+//!
+//! # Examples
+//!
+//! ```
+//! use slab::lru::LruSlab;
+//! use std::sync::mpsc;
+//!
+//! let (tx, rx) = mpsc::channel();
+//! let mut cache = LruSlab::new(2).on_evict(move |key, _value| tx.send(key).unwrap());
+//!
+//! cache.insert("a", 1);
+//! cache.insert("b", 2);
+//! assert_eq!(cache.get("a"), Some(&1));
+//!
+//! // "b" is now the least recently used entry
+//! cache.insert("c", 3);
+//! assert_eq!(rx.try_recv(), Ok("b"));
+//! assert!(!cache.contains_key("b"));
+//! ```
+
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::Hash;
+
+use crate::linked::SlabList;
+
+type EvictFn<K, V> = Box<dyn FnMut(K, V) + Send>;
+
+/// A cache that holds at most a fixed number of entries and evicts the least
+/// recently used one when full.
+///
+/// See the [module documentation] for more details.
+///
+/// [module documentation]: index.html
+pub struct LruSlab<K, V> {
+    // Entries from most to least recently used
+    list: SlabList<(K, V)>,
+    index: HashMap<K, usize>,
+    capacity: usize,
+    on_evict: Option<EvictFn<K, V>>,
+}
+
+impl<K, V> LruSlab<K, V>
+where
+    K: Hash + Eq + Clone,
+{
+    /// Construct a new, empty cache holding at most `capacity` entries.
+    ///
+    /// Space for `capacity` entries is allocated up front.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is 0.
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "an LRU cache needs a capacity of at least 1");
+        Self {
+            list: SlabList::with_capacity(capacity),
+            index: HashMap::with_capacity(capacity),
+            capacity,
+            on_evict: None,
+        }
+    }
+
+    /// Set a callback that is called with every entry evicted to make room
+    /// for a new one.
+    ///
+    /// Entries removed explicitly, replaced, or dropped with the cache are
+    /// not passed to the callback.
+    pub fn on_evict<F>(mut self, on_evict: F) -> Self
+    where
+        F: FnMut(K, V) + Send + 'static,
+    {
+        self.on_evict = Some(Box::new(on_evict));
+        self
+    }
+
+    /// Return the maximum number of entries.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Return the number of entries.
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    /// Return `true` if there are no entries.
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    /// Return `true` if the cache holds an entry for the given key.
+    ///
+    /// This does not count as a use of the entry.
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.index.contains_key(key)
+    }
+
+    /// Insert an entry and mark it as the most recently used one.
+    ///
+    /// If the cache already held an entry for `key`, its value is replaced
+    /// and returned. Otherwise, if the cache is full, the least recently used
+    /// entry is evicted first.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        if let Some(&slot) = self.index.get(&key) {
+            self.list.move_to_front(slot);
+            return Some(std::mem::replace(&mut self.list[slot].1, value));
+        }
+
+        if self.list.len() == self.capacity {
+            if let Some((key, value)) = self.pop_lru() {
+                if let Some(on_evict) = &mut self.on_evict {
+                    on_evict(key, value);
+                }
+            }
+        }
+
+        let slot = self.list.push_front((key.clone(), value));
+        self.index.insert(key, slot);
+        None
+    }
+
+    /// Return a reference to the value for the given key and mark the entry
+    /// as the most recently used one.
+    pub fn get<Q>(&mut self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let slot = *self.index.get(key)?;
+        self.list.move_to_front(slot);
+        Some(&self.list[slot].1)
+    }
+
+    /// Return a mutable reference to the value for the given key and mark
+    /// the entry as the most recently used one.
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let slot = *self.index.get(key)?;
+        self.list.move_to_front(slot);
+        Some(&mut self.list[slot].1)
+    }
+
+    /// Return a reference to the value for the given key without marking the
+    /// entry as used.
+    pub fn peek<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let slot = *self.index.get(key)?;
+        Some(&self.list[slot].1)
+    }
+
+    /// Return the least recently used entry without marking it as used.
+    pub fn peek_lru(&self) -> Option<(&K, &V)> {
+        let (key, value) = self.list.back()?;
+        Some((key, value))
+    }
+
+    /// Remove the entry for the given key, returning its value.
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let slot = self.index.remove(key)?;
+        Some(self.list.remove(slot).1)
+    }
+
+    /// Remove and return the least recently used entry.
+    ///
+    /// The eviction callback is not called.
+    pub fn pop_lru(&mut self) -> Option<(K, V)> {
+        let (key, value) = self.list.pop_back()?;
+        self.index.remove(&key);
+        Some((key, value))
+    }
+
+    /// Remove all entries without calling the eviction callback.
+    pub fn clear(&mut self) {
+        self.list.clear();
+        self.index.clear();
+    }
+
+    /// Return an iterator over the entries from the most to the least
+    /// recently used one, without marking them as used.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (&K, &V)> + ExactSizeIterator + '_ {
+        self.list.iter().map(|(_, (key, value))| (key, value))
+    }
+}
+
+impl<K, V> fmt::Debug for LruSlab<K, V>
+where
+    K: Hash + Eq + Clone + fmt::Debug,
+    V: fmt::Debug,
+{
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if fmt.alternate() {
+            fmt.debug_map().entries(self.iter()).finish()
+        } else {
+            fmt.debug_struct("LruSlab")
+                .field("len", &self.len())
+                .field("cap", &self.capacity)
+                .finish()
+        }
+    }
+}
@@ -0,0 +1,381 @@
+//! A slab whose entries are `Option<T>`.
+//!
+//! Every entry of a [`Slab`](crate::Slab) has room for either a value or the
+//! key of the next vacant entry, plus a tag telling them apart. For types with
+//! a niche, such as `Box`, references or `NonZeroU32`, `Option<T>` is no
+//! bigger than `T`, so [`NicheSlab`] stores `Option<T>` entries and keeps the
+//! vacant keys out of line in a separate stack instead. Occupied entries cost
+//! only the size of `T`, and vacant ones an extra `usize` in the stack while
+//! they are vacant.
+//!
+//! Keys are handed out and reused in the same order as by `Slab`.
+//!
+//! # Examples
+//!
+//! ```
+//! use slab::niche::NicheSlab;
+//! use std::mem;
+//!
+//! let mut slab = NicheSlab::new();
+//! let a = slab.insert(Box::new(1));
+//! let b = slab.insert(Box::new(2));
+//! slab.remove(a);
+//! assert_eq!(slab.insert(Box::new(3)), a);
+//! assert_eq!(*slab[b], 2);
+//!
+//! assert_eq!(mem::size_of::<Option<Box<i32>>>(), mem::size_of::<Box<i32>>());
+//! ```
+
+use alloc::vec::Vec;
+use core::iter::{Enumerate, FusedIterator};
+use core::{cmp, fmt, mem, ops, slice};
+
+/// Pre-allocated storage for a uniform data type, without per-entry
+/// overhead for types with a niche.
+///
+/// See the [module documentation] for more details.
+///
+/// [module documentation]: index.html
+pub struct NicheSlab<T> {
+    pub(crate) entries: Vec<Option<T>>,
+
+    // Vacant keys, the next one to be reused last
+    pub(crate) free: Vec<usize>,
+}
+
+/// An iterator over the values stored in a [`NicheSlab`].
+pub struct Iter<'a, T> {
+    entries: Enumerate<slice::Iter<'a, Option<T>>>,
+    len: usize,
+}
+
+/// A mutable iterator over the values stored in a [`NicheSlab`].
+pub struct IterMut<'a, T> {
+    entries: Enumerate<slice::IterMut<'a, Option<T>>>,
+    len: usize,
+}
+
+impl<T> NicheSlab<T> {
+    /// Construct a new, empty `NicheSlab`.
+    pub const fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+            free: Vec::new(),
+        }
+    }
+
+    /// Construct a new, empty `NicheSlab` with the specified capacity.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            entries: Vec::with_capacity(capacity),
+            free: Vec::new(),
+        }
+    }
+
+    /// Return the number of values the slab can store without reallocating.
+    pub fn capacity(&self) -> usize {
+        self.entries.capacity()
+    }
+
+    /// Reserve capacity for at least `additional` more values.
+    ///
+    /// Vacant entries count towards the reserved capacity.
+    pub fn reserve(&mut self, additional: usize) {
+        self.entries
+            .reserve(additional.saturating_sub(self.free.len()));
+    }
+
+    /// Return the number of stored values.
+    pub fn len(&self) -> usize {
+        self.entries.len() - self.free.len()
+    }
+
+    /// Return `true` if there are no values stored in the slab.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Remove all values from the slab.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.free.clear();
+    }
+
+    /// Return `true` if a value is associated with the given key.
+    pub fn contains(&self, key: usize) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Return a reference to the value associated with the given key.
+    ///
+    /// If the given key is not associated with a value, then `None` is
+    /// returned.
+    pub fn get(&self, key: usize) -> Option<&T> {
+        self.entries.get(key)?.as_ref()
+    }
+
+    /// Return a mutable reference to the value associated with the given key.
+    ///
+    /// If the given key is not associated with a value, then `None` is
+    /// returned.
+    pub fn get_mut(&mut self, key: usize) -> Option<&mut T> {
+        self.entries.get_mut(key)?.as_mut()
+    }
+
+    /// Get the key for an element in the slab.
+    ///
+    /// This is a constant-time operation because the key is calculated from
+    /// the reference with pointer arithmetic.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the reference does not point to an element of the slab.
+    #[cfg_attr(not(slab_no_track_caller), track_caller)]
+    pub fn key_of(&self, present_element: &T) -> usize {
+        let element_ptr = present_element as *const T as usize;
+        let base_ptr = self.entries.as_ptr() as usize;
+        // Use wrapping subtraction in case the reference is bad. The division
+        // rounds away any offset of `T` inside `Option<T>`, except for
+        // zero-sized types at the very end of the entry, which belong to the
+        // previous key.
+        let byte_offset = element_ptr.wrapping_sub(base_ptr);
+        let key = byte_offset / cmp::max(mem::size_of::<Option<T>>(), 1);
+        let is_element = |key: usize| match self.get(key) {
+            Some(value) => value as *const T as usize == element_ptr,
+            None => false,
+        };
+        if is_element(key) {
+            key
+        } else if key > 0 && is_element(key - 1) {
+            key - 1
+        } else {
+            panic!("The reference points to a value outside this slab")
+        }
+    }
+
+    /// Return the key of the next vacant entry.
+    pub fn vacant_key(&self) -> usize {
+        self.free.last().copied().unwrap_or(self.entries.len())
+    }
+
+    /// Insert a value in the slab, returning the key assigned to the value.
+    pub fn insert(&mut self, value: T) -> usize {
+        match self.free.pop() {
+            Some(key) => {
+                self.entries[key] = Some(value);
+                key
+            }
+            None => {
+                self.entries.push(Some(value));
+                self.entries.len() - 1
+            }
+        }
+    }
+
+    /// Remove and return the value associated with the given key.
+    ///
+    /// The key is then released and may be associated with future stored
+    /// values. If the given key is not associated with a value, then `None`
+    /// is returned.
+    pub fn try_remove(&mut self, key: usize) -> Option<T> {
+        let value = self.entries.get_mut(key)?.take()?;
+        self.free.push(key);
+        Some(value)
+    }
+
+    /// Remove and return the value associated with the given key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `key` is not associated with a value.
+    #[cfg_attr(not(slab_no_track_caller), track_caller)]
+    pub fn remove(&mut self, key: usize) -> T {
+        self.try_remove(key).expect("invalid key")
+    }
+
+    /// Return an iterator over the keys and values in key order.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            entries: self.entries.iter().enumerate(),
+            len: self.len(),
+        }
+    }
+
+    /// Return a mutable iterator over the keys and values in key order.
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        let len = self.len();
+        IterMut {
+            entries: self.entries.iter_mut().enumerate(),
+            len,
+        }
+    }
+}
+
+impl<T> Default for NicheSlab<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Clone for NicheSlab<T>
+where
+    T: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            entries: self.entries.clone(),
+            free: self.free.clone(),
+        }
+    }
+}
+
+impl<T> ops::Index<usize> for NicheSlab<T> {
+    type Output = T;
+
+    #[cfg_attr(not(slab_no_track_caller), track_caller)]
+    fn index(&self, key: usize) -> &T {
+        self.get(key).expect("invalid key")
+    }
+}
+
+impl<T> ops::IndexMut<usize> for NicheSlab<T> {
+    #[cfg_attr(not(slab_no_track_caller), track_caller)]
+    fn index_mut(&mut self, key: usize) -> &mut T {
+        self.get_mut(key).expect("invalid key")
+    }
+}
+
+impl<'a, T> IntoIterator for &'a NicheSlab<T> {
+    type Item = (usize, &'a T);
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut NicheSlab<T> {
+    type Item = (usize, &'a mut T);
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> IterMut<'a, T> {
+        self.iter_mut()
+    }
+}
+
+impl<T> fmt::Debug for NicheSlab<T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if fmt.alternate() {
+            fmt.debug_map().entries(self.iter()).finish()
+        } else {
+            fmt.debug_struct("NicheSlab")
+                .field("len", &self.len())
+                .field("cap", &self.capacity())
+                .finish()
+        }
+    }
+}
+
+// ===== Iter =====
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = (usize, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for (key, entry) in &mut self.entries {
+            if let Some(value) = entry {
+                self.len -= 1;
+                return Some((key, value));
+            }
+        }
+        debug_assert_eq!(self.len, 0);
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for Iter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        while let Some((key, entry)) = self.entries.next_back() {
+            if let Some(value) = entry {
+                self.len -= 1;
+                return Some((key, value));
+            }
+        }
+        debug_assert_eq!(self.len, 0);
+        None
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+impl<T> FusedIterator for Iter<'_, T> {}
+
+impl<T> Clone for Iter<'_, T> {
+    fn clone(&self) -> Self {
+        Self {
+            entries: self.entries.clone(),
+            len: self.len,
+        }
+    }
+}
+
+impl<T> fmt::Debug for Iter<'_, T> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("Iter")
+            .field("remaining", &self.len)
+            .finish()
+    }
+}
+
+// ===== IterMut =====
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = (usize, &'a mut T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for (key, entry) in &mut self.entries {
+            if let Some(value) = entry {
+                self.len -= 1;
+                return Some((key, value));
+            }
+        }
+        debug_assert_eq!(self.len, 0);
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for IterMut<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        while let Some((key, entry)) = self.entries.next_back() {
+            if let Some(value) = entry {
+                self.len -= 1;
+                return Some((key, value));
+            }
+        }
+        debug_assert_eq!(self.len, 0);
+        None
+    }
+}
+
+impl<T> ExactSizeIterator for IterMut<'_, T> {}
+
+impl<T> FusedIterator for IterMut<'_, T> {}
+
+impl<T> fmt::Debug for IterMut<'_, T> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("IterMut")
+            .field("remaining", &self.len)
+            .finish()
+    }
+}
@@ -0,0 +1,239 @@
+//! A slab that remembers the order in which values were inserted.
+//!
+//! [`Slab::iter`](crate::Slab::iter) returns values in key order, which
+//! after keys are reused says nothing about when the values were inserted.
+//! [`OrderedSlab`] threads a linked list through its entries, so iteration,
+//! draining and serialization follow insertion order instead, while keys stay
+//! stable and removal by key still takes O(1) time.
+//!
+//! # Examples
+//!
+//! ```
+//! use slab::ordered::OrderedSlab;
+//!
+//! let mut slab = OrderedSlab::new();
+//! let a = slab.insert("a");
+//! slab.insert("b");
+//! slab.remove(a);
+//!
+//! // The key of "a" is reused, but "c" is still last
+//! assert_eq!(slab.insert("c"), a);
+//! let values: Vec<_> = slab.iter().map(|(_, &value)| value).collect();
+//! assert_eq!(values, ["b", "c"]);
+//! ```
+
+use core::fmt;
+use core::iter::FusedIterator;
+use core::ops;
+
+use crate::linked::{self, SlabList};
+
+/// Pre-allocated storage that iterates in insertion order.
+///
+/// See the [module documentation] for more details.
+///
+/// [module documentation]: index.html
+pub struct OrderedSlab<T> {
+    pub(crate) list: SlabList<T>,
+}
+
+/// An iterator over the values of an [`OrderedSlab`] in insertion order.
+pub type Iter<'a, T> = linked::Iter<'a, T>;
+
+/// A draining iterator for [`OrderedSlab`], returned by
+/// [`OrderedSlab::drain`].
+pub struct Drain<'a, T> {
+    list: &'a mut SlabList<T>,
+}
+
+impl<T> OrderedSlab<T> {
+    /// Construct a new, empty `OrderedSlab`.
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    /// Construct a new, empty `OrderedSlab` with the specified capacity.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            list: SlabList::with_capacity(capacity),
+        }
+    }
+
+    /// Return the number of values the slab can store without reallocating.
+    pub fn capacity(&self) -> usize {
+        self.list.capacity()
+    }
+
+    /// Return the number of stored values.
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    /// Return `true` if there are no values stored in the slab.
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    /// Remove all values from the slab.
+    pub fn clear(&mut self) {
+        self.list.clear();
+    }
+
+    /// Return `true` if a value is associated with the given key.
+    pub fn contains(&self, key: usize) -> bool {
+        self.list.contains(key)
+    }
+
+    /// Return a reference to the value associated with the given key.
+    ///
+    /// If the given key is not associated with a value, then `None` is
+    /// returned.
+    pub fn get(&self, key: usize) -> Option<&T> {
+        self.list.get(key)
+    }
+
+    /// Return a mutable reference to the value associated with the given key.
+    ///
+    /// If the given key is not associated with a value, then `None` is
+    /// returned.
+    pub fn get_mut(&mut self, key: usize) -> Option<&mut T> {
+        self.list.get_mut(key)
+    }
+
+    /// Return the key of the oldest value.
+    pub fn first_key(&self) -> Option<usize> {
+        self.list.front_key()
+    }
+
+    /// Return the key of the newest value.
+    pub fn last_key(&self) -> Option<usize> {
+        self.list.back_key()
+    }
+
+    /// Insert a value after all other values, returning the key assigned to
+    /// the value.
+    ///
+    /// Keys are reused like they are by `Slab`, so the key says nothing about
+    /// the position of the value.
+    pub fn insert(&mut self, value: T) -> usize {
+        self.list.push_back(value)
+    }
+
+    /// Remove and return the value associated with the given key.
+    ///
+    /// If the given key is not associated with a value, then `None` is
+    /// returned.
+    pub fn try_remove(&mut self, key: usize) -> Option<T> {
+        self.list.try_remove(key)
+    }
+
+    /// Remove and return the value associated with the given key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `key` is not associated with a value.
+    #[cfg_attr(not(slab_no_track_caller), track_caller)]
+    pub fn remove(&mut self, key: usize) -> T {
+        self.list.remove(key)
+    }
+
+    /// Return an iterator over the keys and values in insertion order.
+    pub fn iter(&self) -> Iter<'_, T> {
+        self.list.iter()
+    }
+
+    /// Return a draining iterator that removes all values from the slab and
+    /// yields them in insertion order.
+    ///
+    /// All values are removed even if the iterator is not exhausted.
+    pub fn drain(&mut self) -> Drain<'_, T> {
+        Drain {
+            list: &mut self.list,
+        }
+    }
+}
+
+impl<T> Default for OrderedSlab<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> ops::Index<usize> for OrderedSlab<T> {
+    type Output = T;
+
+    #[cfg_attr(not(slab_no_track_caller), track_caller)]
+    fn index(&self, key: usize) -> &T {
+        &self.list[key]
+    }
+}
+
+impl<T> ops::IndexMut<usize> for OrderedSlab<T> {
+    #[cfg_attr(not(slab_no_track_caller), track_caller)]
+    fn index_mut(&mut self, key: usize) -> &mut T {
+        &mut self.list[key]
+    }
+}
+
+impl<'a, T> IntoIterator for &'a OrderedSlab<T> {
+    type Item = (usize, &'a T);
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<T> fmt::Debug for OrderedSlab<T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if fmt.alternate() {
+            fmt.debug_map().entries(self.iter()).finish()
+        } else {
+            fmt.debug_struct("OrderedSlab")
+                .field("len", &self.len())
+                .field("cap", &self.capacity())
+                .finish()
+        }
+    }
+}
+
+// ===== Drain =====
+
+impl<T> Iterator for Drain<'_, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.list.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.list.len(), Some(self.list.len()))
+    }
+}
+
+impl<T> DoubleEndedIterator for Drain<'_, T> {
+    fn next_back(&mut self) -> Option<T> {
+        self.list.pop_back()
+    }
+}
+
+impl<T> ExactSizeIterator for Drain<'_, T> {}
+
+impl<T> FusedIterator for Drain<'_, T> {}
+
+impl<T> Drop for Drain<'_, T> {
+    fn drop(&mut self) {
+        self.list.clear();
+    }
+}
+
+impl<T> fmt::Debug for Drain<'_, T> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("Drain")
+            .field("remaining", &self.list.len())
+            .finish()
+    }
+}
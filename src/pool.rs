@@ -0,0 +1,326 @@
+//! A slab that keeps removed values around for reuse.
+//!
+//! Removing a value from a [`Slab`] drops it, so values that own allocations,
+//! such as `Vec<u8>` buffers, are freed and allocated again over and over. A
+//! [`Pool`] instead keeps the value of a removed entry in place, and hands it
+//! to the next [`Pool::insert_with`] that reuses the key, so it can be
+//! reinitialized rather than reallocated.
+//!
+//! # Examples
+//!
+//! ```
+//! use slab::pool::Pool;
+//!
+//! let mut pool: Pool<Vec<u8>> = Pool::new();
+//!
+//! let new_buffer = |recycled: Option<&mut Vec<u8>>| match recycled {
+//!     Some(buf) => {
+//!         buf.clear();
+//!         None
+//!     }
+//!     None => Some(Vec::with_capacity(1500)),
+//! };
+//!
+//! let key = pool.insert_with(new_buffer);
+//! pool[key].extend_from_slice(b"packet");
+//! pool.remove(key);
+//!
+//! let key = pool.insert_with(new_buffer);
+//! assert!(pool[key].is_empty());
+//! assert!(pool[key].capacity() >= 1500);
+//! ```
+
+use alloc::vec::Vec;
+use core::iter::FusedIterator;
+use core::{fmt, ops};
+
+use crate::Slab;
+
+/// Pre-allocated storage that recycles the values of removed entries.
+///
+/// See the [module documentation] for more details.
+///
+/// [module documentation]: index.html
+pub struct Pool<T> {
+    // Tracks which keys are occupied and links the vacant ones
+    keys: Slab<()>,
+
+    // The value of every occupied entry, and the recycled value of vacant
+    // entries that had one
+    values: Vec<Option<T>>,
+}
+
+/// An iterator over the values stored in a [`Pool`].
+pub struct Iter<'a, T> {
+    keys: crate::Iter<'a, ()>,
+    values: &'a [Option<T>],
+}
+
+impl<T> Pool<T> {
+    /// Construct a new, empty `Pool`.
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    /// Construct a new, empty `Pool` with the specified capacity.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            keys: Slab::with_capacity(capacity),
+            values: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Return the number of values the pool can store without reallocating.
+    pub fn capacity(&self) -> usize {
+        self.keys.capacity()
+    }
+
+    /// Return the number of stored values.
+    ///
+    /// Recycled values are not counted.
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Return `true` if there are no values stored in the pool.
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Return a reference to the value associated with the given key.
+    ///
+    /// If the given key is not associated with a value, then `None` is
+    /// returned.
+    pub fn get(&self, key: usize) -> Option<&T> {
+        if self.keys.contains(key) {
+            self.values[key].as_ref()
+        } else {
+            None
+        }
+    }
+
+    /// Return a mutable reference to the value associated with the given key.
+    ///
+    /// If the given key is not associated with a value, then `None` is
+    /// returned.
+    pub fn get_mut(&mut self, key: usize) -> Option<&mut T> {
+        if self.keys.contains(key) {
+            self.values[key].as_mut()
+        } else {
+            None
+        }
+    }
+
+    /// Return `true` if a value is associated with the given key.
+    pub fn contains(&self, key: usize) -> bool {
+        self.keys.contains(key)
+    }
+
+    /// Insert a value in the pool, returning the key assigned to the value.
+    ///
+    /// Any recycled value of the reused entry is dropped. Use
+    /// [`insert_with`](Pool::insert_with) to reuse it instead.
+    pub fn insert(&mut self, value: T) -> usize {
+        self.insert_with(|_| Some(value))
+    }
+
+    /// Insert a value initialized by `f`, returning the key assigned to it.
+    ///
+    /// `f` receives a mutable reference to the value that was last removed
+    /// from the reused entry, if there is one, so that it can be
+    /// reinitialized in place. `f` returns `None` to store the recycled value
+    /// as it left it, or `Some` with a new value to store instead, which
+    /// drops the recycled value.
+    ///
+    /// If `f` panics, the pool is left unchanged, apart from whatever `f` did
+    /// to the recycled value, which is kept for reuse.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `f` returns `None` although there is no recycled value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use slab::pool::Pool;
+    ///
+    /// let mut pool = Pool::new();
+    /// let key = pool.insert(String::from("hello"));
+    /// pool.remove(key);
+    ///
+    /// let key = pool.insert_with(|recycled| {
+    ///     let s = recycled?;
+    ///     s.clear();
+    ///     s.push_str("world");
+    ///     None
+    /// });
+    /// assert_eq!(pool[key], "world");
+    /// ```
+    #[cfg_attr(not(slab_no_track_caller), track_caller)]
+    pub fn insert_with<F>(&mut self, f: F) -> usize
+    where
+        F: FnOnce(Option<&mut T>) -> Option<T>,
+    {
+        let key = self.keys.vacant_key();
+        if key == self.values.len() {
+            self.values.push(None);
+        }
+        let slot = &mut self.values[key];
+        match f(slot.as_mut()) {
+            Some(value) => *slot = Some(value),
+            None => assert!(slot.is_some(), "no value to insert"),
+        }
+        self.keys.insert(());
+        key
+    }
+
+    /// Remove the value associated with the given key, keeping it for reuse
+    /// by a later insertion.
+    ///
+    /// Returns `true` if a value was removed.
+    pub fn try_remove(&mut self, key: usize) -> bool {
+        self.keys.try_remove(key).is_some()
+    }
+
+    /// Remove the value associated with the given key, keeping it for reuse
+    /// by a later insertion.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `key` is not associated with a value.
+    #[cfg_attr(not(slab_no_track_caller), track_caller)]
+    pub fn remove(&mut self, key: usize) {
+        assert!(self.try_remove(key), "invalid key");
+    }
+
+    /// Remove and return the value associated with the given key, without
+    /// keeping it for reuse.
+    ///
+    /// If the given key is not associated with a value, then `None` is
+    /// returned.
+    pub fn take(&mut self, key: usize) -> Option<T> {
+        self.keys.try_remove(key)?;
+        self.values[key].take()
+    }
+
+    /// Remove all values, keeping them for reuse.
+    pub fn clear(&mut self) {
+        self.keys.clear();
+    }
+
+    /// Drop all recycled values.
+    ///
+    /// Values that are still stored in the pool are not affected.
+    pub fn clear_recycled(&mut self) {
+        for (key, value) in self.values.iter_mut().enumerate() {
+            if !self.keys.contains(key) {
+                *value = None;
+            }
+        }
+    }
+
+    /// Return an iterator over the stored values and their keys.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            keys: self.keys.iter(),
+            values: &self.values,
+        }
+    }
+}
+
+impl<T> Default for Pool<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> ops::Index<usize> for Pool<T> {
+    type Output = T;
+
+    #[cfg_attr(not(slab_no_track_caller), track_caller)]
+    fn index(&self, key: usize) -> &T {
+        self.get(key).expect("invalid key")
+    }
+}
+
+impl<T> ops::IndexMut<usize> for Pool<T> {
+    #[cfg_attr(not(slab_no_track_caller), track_caller)]
+    fn index_mut(&mut self, key: usize) -> &mut T {
+        self.get_mut(key).expect("invalid key")
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Pool<T> {
+    type Item = (usize, &'a T);
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<T> fmt::Debug for Pool<T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if fmt.alternate() {
+            fmt.debug_map().entries(self.iter()).finish()
+        } else {
+            fmt.debug_struct("Pool")
+                .field("len", &self.len())
+                .field("cap", &self.capacity())
+                .finish()
+        }
+    }
+}
+
+// ===== Iter =====
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = (usize, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (key, ()) = self.keys.next()?;
+        Some((key, self.values[key].as_ref()?))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.keys.size_hint()
+    }
+}
+
+impl<T> DoubleEndedIterator for Iter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let (key, ()) = self.keys.next_back()?;
+        Some((key, self.values[key].as_ref()?))
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {
+    fn len(&self) -> usize {
+        self.keys.len()
+    }
+}
+
+impl<T> FusedIterator for Iter<'_, T> {}
+
+impl<T> Clone for Iter<'_, T> {
+    fn clone(&self) -> Self {
+        Self {
+            keys: self.keys.clone(),
+            values: self.values,
+        }
+    }
+}
+
+impl<T> fmt::Debug for Iter<'_, T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("Iter")
+            .field("remaining", &self.len())
+            .finish()
+    }
+}
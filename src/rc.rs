@@ -0,0 +1,585 @@
+//! Slabs that hand out reference-counted handles.
+//!
+//! Pairing a [`Slab`](crate::Slab) with manual reference counts makes it easy
+//! to leak keys by forgetting to remove a value once the last user is gone.
+//! The slabs in this module return a handle from `insert` instead. Handles
+//! can be cloned, dereference to the value, and release the key back to the
+//! slab's vacant list when the last one is dropped.
+//!
+//! Values are stored in the slab's own entries, next to the number of handles
+//! to them, so inserting a value doesn't allocate unless the slab is full.
+//! Unlike a `Slab`, which moves its entries when it grows, the entries are
+//! kept in pages that never move: the first page has room for 32 values and
+//! each further page for twice as many as the one before. Handles point
+//! straight at their entry, so dereferencing one doesn't touch the rest of
+//! the slab. The pages are released once the slab and all handles to its
+//! values are dropped.
+//!
+//! A key can also be turned back into a handle with `get` for as long as any
+//! handle to its value is alive.
+//!
+//! [`SlabRc`] is for use within a single thread, and [`SlabArc`] can be
+//! shared between threads.
+//!
+//! # Examples
+//!
+//! ```
+//! use slab::rc::SlabRc;
+//!
+//! let slab = SlabRc::new();
+//!
+//! let hello = slab.insert("hello");
+//! let key = hello.key();
+//! assert_eq!(*hello, "hello");
+//!
+//! let other = slab.get(key).unwrap();
+//! drop(hello);
+//! assert!(slab.contains(key));
+//!
+//! drop(other);
+//! assert!(!slab.contains(key));
+//! assert!(slab.is_empty());
+//! ```
+
+use alloc::boxed::Box;
+use alloc::rc::Rc;
+use alloc::vec::Vec;
+use core::cell::{Cell, RefCell, UnsafeCell};
+use core::mem::MaybeUninit;
+use core::ptr::{self, NonNull};
+use core::{fmt, ops};
+
+#[cfg(feature = "std")]
+use alloc::sync::{Arc, Mutex, MutexGuard, PoisonError};
+#[cfg(feature = "std")]
+use core::sync::atomic::{self, AtomicUsize, Ordering};
+
+// The first page has `1 << FIRST_PAGE_BITS` entries
+const FIRST_PAGE_BITS: u32 = 5;
+
+fn page_len(page: usize) -> usize {
+    1 << (FIRST_PAGE_BITS as usize + page)
+}
+
+struct Slot<T, C> {
+    // Number of handles to the value, 0 while the slot is vacant
+    refs: C,
+
+    // Key of the next vacant slot while this one is vacant
+    next: Cell<usize>,
+
+    // Initialized while there are handles to the value
+    value: UnsafeCell<MaybeUninit<T>>,
+}
+
+struct Slots<T, C> {
+    // Pages allocated as `Box<[Slot<T, C>]>` with `page_len(i)` slots each
+    pages: Vec<NonNull<Slot<T, C>>>,
+
+    // Number of slots that have been occupied at some point
+    used: usize,
+
+    // Key of the first vacant slot, equal to `used` if no slot below it is
+    // vacant
+    next: usize,
+
+    // Number of occupied slots
+    len: usize,
+}
+
+impl<T, C: Default> Slots<T, C> {
+    fn with_capacity(capacity: usize) -> Self {
+        let mut slots = Self {
+            pages: Vec::new(),
+            used: 0,
+            next: 0,
+            len: 0,
+        };
+        while slots.capacity() < capacity {
+            slots.grow();
+        }
+        slots
+    }
+
+    fn capacity(&self) -> usize {
+        page_len(self.pages.len()) - page_len(0)
+    }
+
+    fn grow(&mut self) {
+        let page: Box<[Slot<T, C>]> = (0..page_len(self.pages.len()))
+            .map(|_| Slot {
+                refs: C::default(),
+                next: Cell::new(0),
+                value: UnsafeCell::new(MaybeUninit::uninit()),
+            })
+            .collect();
+        let page = Box::into_raw(page).cast::<Slot<T, C>>();
+        // Safety: boxes are never null
+        self.pages.push(unsafe { NonNull::new_unchecked(page) });
+    }
+
+    fn slot(&self, key: usize) -> Option<&Slot<T, C>> {
+        if key >= self.used {
+            return None;
+        }
+        let index = key + page_len(0);
+        let page = (usize::BITS - 1 - index.leading_zeros() - FIRST_PAGE_BITS) as usize;
+        // Safety: all slots below `used` are in allocated pages, and all
+        // accesses to slots go through shared references
+        Some(unsafe { &*self.pages[page].as_ptr().add(index - page_len(page)) })
+    }
+
+    // Move a value into a vacant slot and return its key and slot. The caller
+    // sets the number of handles.
+    fn insert(&mut self, value: T) -> (usize, NonNull<Slot<T, C>>) {
+        let key = self.next;
+        if key == self.used {
+            if self.used == self.capacity() {
+                self.grow();
+            }
+            self.used += 1;
+            self.next = key + 1;
+        } else {
+            self.next = self.slot(key).unwrap().next.get();
+        }
+        self.len += 1;
+
+        let slot = self.slot(key).unwrap();
+        // Safety: the slot is vacant, so there are no handles to it
+        unsafe { (*slot.value.get()).write(value) };
+        (key, NonNull::from(slot))
+    }
+
+    // Return the slot of a value whose last handle was dropped to the vacant
+    // list. The value must have been moved out already.
+    fn release(&mut self, key: usize) {
+        self.slot(key).unwrap().next.set(self.next);
+        self.next = key;
+        self.len -= 1;
+    }
+}
+
+impl<T, C> Drop for Slots<T, C> {
+    fn drop(&mut self) {
+        // Every value has a handle, which keeps the slots alive, so all slots
+        // are vacant by now
+        for (i, page) in self.pages.iter().enumerate() {
+            let page = ptr::slice_from_raw_parts_mut(page.as_ptr(), page_len(i));
+            drop(unsafe { Box::from_raw(page) });
+        }
+    }
+}
+
+// Return a reference to the value of a slot with handles
+//
+// Safety: the slot must have handles, and stay alive while the value is used
+unsafe fn slot_value<'a, T, C>(slot: NonNull<Slot<T, C>>) -> &'a T {
+    (*slot.as_ref().value.get()).assume_init_ref()
+}
+
+/// A slab whose values are kept alive by [`RcHandle`]s.
+///
+/// Cloning a `SlabRc` returns another reference to the same slab.
+///
+/// See the [module documentation] for more details.
+///
+/// [module documentation]: index.html
+pub struct SlabRc<T> {
+    slots: Rc<RefCell<Slots<T, Cell<usize>>>>,
+}
+
+/// A handle to a value stored in a [`SlabRc`].
+///
+/// The value is removed from the slab when the last handle to it is dropped.
+pub struct RcHandle<T> {
+    slots: Rc<RefCell<Slots<T, Cell<usize>>>>,
+    slot: NonNull<Slot<T, Cell<usize>>>,
+    key: usize,
+}
+
+impl<T> SlabRc<T> {
+    /// Construct a new, empty `SlabRc`.
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    /// Construct a new, empty `SlabRc` with room for at least `capacity`
+    /// values.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            slots: Rc::new(RefCell::new(Slots::with_capacity(capacity))),
+        }
+    }
+
+    /// Return the number of values the slab can store without allocating.
+    pub fn capacity(&self) -> usize {
+        self.slots.borrow().capacity()
+    }
+
+    /// Return the number of values that still have handles.
+    pub fn len(&self) -> usize {
+        self.slots.borrow().len
+    }
+
+    /// Return `true` if there are no values stored in the slab.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Insert a value into the slab, returning the only handle to it.
+    ///
+    /// The key of the value is available through [`RcHandle::key`].
+    pub fn insert(&self, value: T) -> RcHandle<T> {
+        let (key, slot) = self.slots.borrow_mut().insert(value);
+        // Safety: the slot stays alive as long as the slots do
+        unsafe { slot.as_ref() }.refs.set(1);
+        RcHandle {
+            slots: self.slots.clone(),
+            slot,
+            key,
+        }
+    }
+
+    /// Return a new handle to the value associated with the given key.
+    ///
+    /// If the given key is not associated with a value, then `None` is
+    /// returned.
+    pub fn get(&self, key: usize) -> Option<RcHandle<T>> {
+        let slots = self.slots.borrow();
+        let slot = slots.slot(key)?;
+        let refs = slot.refs.get();
+        if refs == 0 {
+            return None;
+        }
+        slot.refs
+            .set(refs.checked_add(1).expect("too many handles"));
+        Some(RcHandle {
+            slots: self.slots.clone(),
+            slot: NonNull::from(slot),
+            key,
+        })
+    }
+
+    /// Return `true` if a value is associated with the given key.
+    pub fn contains(&self, key: usize) -> bool {
+        match self.slots.borrow().slot(key) {
+            Some(slot) => slot.refs.get() > 0,
+            None => false,
+        }
+    }
+}
+
+impl<T> Clone for SlabRc<T> {
+    fn clone(&self) -> Self {
+        Self {
+            slots: self.slots.clone(),
+        }
+    }
+}
+
+impl<T> Default for SlabRc<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> fmt::Debug for SlabRc<T> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("SlabRc")
+            .field("len", &self.len())
+            .field("cap", &self.capacity())
+            .finish()
+    }
+}
+
+impl<T> RcHandle<T> {
+    /// Return the key of the value.
+    pub fn key(&self) -> usize {
+        self.key
+    }
+
+    /// Return `true` if both handles refer to the same value.
+    pub fn ptr_eq(this: &Self, other: &Self) -> bool {
+        this.slot == other.slot
+    }
+}
+
+impl<T> Clone for RcHandle<T> {
+    fn clone(&self) -> Self {
+        // Safety: the handle keeps the slot alive
+        let refs = &unsafe { self.slot.as_ref() }.refs;
+        refs.set(refs.get().checked_add(1).expect("too many handles"));
+        Self {
+            slots: self.slots.clone(),
+            slot: self.slot,
+            key: self.key,
+        }
+    }
+}
+
+impl<T> ops::Deref for RcHandle<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // Safety: the handle keeps the value alive
+        unsafe { slot_value(self.slot) }
+    }
+}
+
+impl<T> fmt::Debug for RcHandle<T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("RcHandle")
+            .field("key", &self.key)
+            .field("value", &**self)
+            .finish()
+    }
+}
+
+impl<T> Drop for RcHandle<T> {
+    fn drop(&mut self) {
+        // Safety: the handle keeps the slot alive
+        let slot = unsafe { self.slot.as_ref() };
+        let refs = slot.refs.get() - 1;
+        slot.refs.set(refs);
+        if refs == 0 {
+            // Safety: this was the last handle, so the value can't be
+            // accessed anymore
+            let value = unsafe { (*slot.value.get()).assume_init_read() };
+            // The value is dropped after the slot is released, so it may
+            // drop handles to the same slab.
+            self.slots.borrow_mut().release(self.key);
+            drop(value);
+        }
+    }
+}
+
+/// A thread-safe slab whose values are kept alive by [`ArcHandle`]s.
+///
+/// The slab is protected by a mutex, which is only locked to insert values,
+/// look up keys, and release keys. Accessing a value through a handle doesn't
+/// lock the slab.
+///
+/// See the [module documentation] for more details.
+///
+/// [module documentation]: index.html
+#[cfg(feature = "std")]
+pub struct SlabArc<T> {
+    slots: Arc<Mutex<Slots<T, AtomicUsize>>>,
+}
+
+/// A handle to a value stored in a [`SlabArc`].
+///
+/// The value is removed from the slab when the last handle to it is dropped.
+#[cfg(feature = "std")]
+pub struct ArcHandle<T> {
+    slots: Arc<Mutex<Slots<T, AtomicUsize>>>,
+    slot: NonNull<Slot<T, AtomicUsize>>,
+    key: usize,
+}
+
+// Values are shared between the threads that hold handles, and dropped by
+// whichever thread drops the last one. The vacant list is only accessed with
+// the mutex locked.
+#[cfg(feature = "std")]
+unsafe impl<T: Send + Sync> Send for SlabArc<T> {}
+#[cfg(feature = "std")]
+unsafe impl<T: Send + Sync> Sync for SlabArc<T> {}
+#[cfg(feature = "std")]
+unsafe impl<T: Send + Sync> Send for ArcHandle<T> {}
+#[cfg(feature = "std")]
+unsafe impl<T: Send + Sync> Sync for ArcHandle<T> {}
+
+#[cfg(feature = "std")]
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    // Slab operations leave the slab consistent even if they panic
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+#[cfg(feature = "std")]
+impl<T> SlabArc<T> {
+    /// Construct a new, empty `SlabArc`.
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    /// Construct a new, empty `SlabArc` with room for at least `capacity`
+    /// values.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            slots: Arc::new(Mutex::new(Slots::with_capacity(capacity))),
+        }
+    }
+
+    /// Return the number of values the slab can store without allocating.
+    pub fn capacity(&self) -> usize {
+        lock(&self.slots).capacity()
+    }
+
+    /// Return the number of values that still have handles.
+    pub fn len(&self) -> usize {
+        lock(&self.slots).len
+    }
+
+    /// Return `true` if there are no values stored in the slab.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Insert a value into the slab, returning the only handle to it.
+    ///
+    /// The key of the value is available through [`ArcHandle::key`].
+    pub fn insert(&self, value: T) -> ArcHandle<T> {
+        let (key, slot) = lock(&self.slots).insert(value);
+        // Safety: the slot stays alive as long as the slots do, and it can't
+        // be accessed by other threads until its handle count is set
+        unsafe { slot.as_ref() }.refs.store(1, Ordering::Relaxed);
+        ArcHandle {
+            slots: self.slots.clone(),
+            slot,
+            key,
+        }
+    }
+
+    /// Return a new handle to the value associated with the given key.
+    ///
+    /// If the given key is not associated with a value, then `None` is
+    /// returned. This includes values whose last handle is being dropped
+    /// concurrently.
+    pub fn get(&self, key: usize) -> Option<ArcHandle<T>> {
+        let slots = lock(&self.slots);
+        let slot = slots.slot(key)?;
+        let mut refs = slot.refs.load(Ordering::Relaxed);
+        loop {
+            if refs == 0 {
+                return None;
+            }
+            match slot.refs.compare_exchange_weak(
+                refs,
+                refs + 1,
+                Ordering::Relaxed,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => break,
+                Err(actual) => refs = actual,
+            }
+        }
+        Some(ArcHandle {
+            slots: self.slots.clone(),
+            slot: NonNull::from(slot),
+            key,
+        })
+    }
+
+    /// Return `true` if a value is associated with the given key.
+    pub fn contains(&self, key: usize) -> bool {
+        match lock(&self.slots).slot(key) {
+            Some(slot) => slot.refs.load(Ordering::Relaxed) > 0,
+            None => false,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T> Clone for SlabArc<T> {
+    fn clone(&self) -> Self {
+        Self {
+            slots: self.slots.clone(),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T> Default for SlabArc<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T> fmt::Debug for SlabArc<T> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("SlabArc")
+            .field("len", &self.len())
+            .field("cap", &self.capacity())
+            .finish()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T> ArcHandle<T> {
+    /// Return the key of the value.
+    pub fn key(&self) -> usize {
+        self.key
+    }
+
+    /// Return `true` if both handles refer to the same value.
+    pub fn ptr_eq(this: &Self, other: &Self) -> bool {
+        this.slot == other.slot
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T> Clone for ArcHandle<T> {
+    fn clone(&self) -> Self {
+        // Safety: the handle keeps the slot alive
+        let refs = unsafe { self.slot.as_ref() }
+            .refs
+            .fetch_add(1, Ordering::Relaxed);
+        if refs > isize::MAX as usize {
+            // Like `Arc`, abort rather than let the count overflow
+            std::process::abort();
+        }
+        Self {
+            slots: self.slots.clone(),
+            slot: self.slot,
+            key: self.key,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T> ops::Deref for ArcHandle<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // Safety: the handle keeps the value alive
+        unsafe { slot_value(self.slot) }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T> fmt::Debug for ArcHandle<T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("ArcHandle")
+            .field("key", &self.key)
+            .field("value", &**self)
+            .finish()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T> Drop for ArcHandle<T> {
+    fn drop(&mut self) {
+        // Safety: the handle keeps the slot alive
+        let slot = unsafe { self.slot.as_ref() };
+        if slot.refs.fetch_sub(1, Ordering::Release) != 1 {
+            return;
+        }
+        // Synchronize with the other handles' uses of the value before
+        // dropping it
+        atomic::fence(Ordering::Acquire);
+        // Safety: this was the last handle, and `get` doesn't create new ones
+        // for values without handles
+        let value = unsafe { (*slot.value.get()).assume_init_read() };
+        // The value is dropped after the slot is released, so it may drop
+        // handles to the same slab.
+        lock(&self.slots).release(self.key);
+        drop(value);
+    }
+}
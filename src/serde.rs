@@ -1,10 +1,36 @@
-use core::fmt;
+//! Serde support for `Slab`.
+//!
+//! This module is private so that `use slab::*` doesn't bring a `serde` that
+//! conflicts with the `serde` crate into scope. Its public items are
+//! re-exported from the crate root.
+
 use core::marker::PhantomData;
+use core::{cmp, fmt, mem};
 
-use serde::de::{Deserialize, Deserializer, MapAccess, Visitor};
+use serde::de::{self, Deserialize, DeserializeSeed, Deserializer, MapAccess, Unexpected, Visitor};
 use serde::ser::{Serialize, SerializeMap, Serializer};
 
-use super::{builder::Builder, Slab};
+use super::{builder::SlabBuilder, Entry, Slab};
+
+pub mod dense;
+pub mod exact;
+mod niche;
+mod ordered;
+
+/// Alternative serde representations for `Slab`.
+///
+/// By default a `Slab` is serialized as a map from keys to values, which only
+/// preserves the occupied entries. The modules below provide other
+/// representations that can be selected per field with
+/// `#[serde(with = "...")]`.
+///
+/// The keys of the default representation determine how much memory the slab
+/// uses, so deserializing it from untrusted input should go through
+/// [`SlabSeed`](crate::SlabSeed), which rejects keys and lengths above
+/// configurable limits.
+pub mod repr {
+    pub use super::{dense, exact};
+}
 
 impl<T> Serialize for Slab<T>
 where
@@ -23,7 +49,256 @@ where
     }
 }
 
-struct SlabVisitor<T>(PhantomData<T>);
+/// Limit the capacity preallocated from a size hint, which may come from
+/// untrusted input, to about 1 MiB.
+pub(crate) fn cautious_capacity<T>(size_hint: Option<usize>) -> usize {
+    const MAX_PREALLOC_BYTES: usize = 1024 * 1024;
+    let entry_size = cmp::max(mem::size_of::<Entry<T>>(), 1);
+    cmp::min(size_hint.unwrap_or(0), MAX_PREALLOC_BYTES / entry_size)
+}
+
+/// Deserialize a `Slab` with limits on its keys, length and capacity.
+///
+/// A `Slab` uses memory proportional to its largest key, so a single entry
+/// with a huge key is enough to make deserialization allocate more memory
+/// than is available. `SlabSeed` fails with a deserialization error instead
+/// when a key, the number of values or the capacity of the slab exceeds the
+/// configured limits.
+///
+/// `SlabSeed` deserializes the default map representation. The same limits
+/// apply to the other representations through [`dense`](SlabSeed::dense),
+/// [`exact`](SlabSeed::exact), [`ordered`](SlabSeed::ordered) and
+/// [`niche`](SlabSeed::niche).
+///
+/// # Examples
+///
+/// ```
+/// use serde::{Deserialize, Deserializer};
+/// use serde::de::DeserializeSeed;
+/// use slab::SlabSeed;
+/// use slab::Slab;
+///
+/// #[derive(Deserialize)]
+/// struct Request {
+///     #[serde(deserialize_with = "bounded")]
+///     items: Slab<String>,
+///     #[serde(deserialize_with = "bounded_exact")]
+///     snapshot: Slab<u32>,
+/// }
+///
+/// fn bounded<'de, D>(deserializer: D) -> Result<Slab<String>, D::Error>
+/// where
+///     D: Deserializer<'de>,
+/// {
+///     SlabSeed::new().max_key(1023).max_len(256).deserialize(deserializer)
+/// }
+///
+/// fn bounded_exact<'de, D>(deserializer: D) -> Result<Slab<u32>, D::Error>
+/// where
+///     D: Deserializer<'de>,
+/// {
+///     SlabSeed::new().max_capacity(4096).exact().deserialize(deserializer)
+/// }
+/// ```
+pub struct SlabSeed<T> {
+    limits: Limits,
+    _marker: PhantomData<T>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Limits {
+    max_key: usize,
+    max_len: usize,
+    max_capacity: usize,
+}
+
+impl<T> SlabSeed<T> {
+    /// Create a seed without any limits, which behaves like
+    /// `Slab::deserialize`.
+    pub fn new() -> Self {
+        Self {
+            limits: Limits::NONE,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Reject keys greater than `max_key`.
+    pub fn max_key(mut self, max_key: usize) -> Self {
+        self.limits.max_key = max_key;
+        self
+    }
+
+    /// Reject input with more than `max_len` values.
+    pub fn max_len(mut self, max_len: usize) -> Self {
+        self.limits.max_len = max_len;
+        self
+    }
+
+    /// Reject input that needs a slab with more than `max_capacity` entries,
+    /// occupied or vacant.
+    ///
+    /// This also rejects keys of `max_capacity` or more, and for the
+    /// [`exact`](SlabSeed::exact) representation a serialized capacity above
+    /// `max_capacity`.
+    pub fn max_capacity(mut self, max_capacity: usize) -> Self {
+        self.limits.max_capacity = max_capacity;
+        self
+    }
+
+    /// Deserialize the [`dense`] representation with these limits.
+    pub fn dense<'de>(self) -> impl DeserializeSeed<'de, Value = Slab<T>>
+    where
+        T: Deserialize<'de>,
+    {
+        dense::DenseVisitor(self.limits, PhantomData)
+    }
+
+    /// Deserialize the [`exact`] representation with these limits.
+    pub fn exact<'de>(self) -> impl DeserializeSeed<'de, Value = Slab<T>>
+    where
+        T: Deserialize<'de>,
+    {
+        exact::ExactVisitor(self.limits, PhantomData)
+    }
+
+    /// Deserialize an [`OrderedSlab`](crate::ordered::OrderedSlab) with these
+    /// limits.
+    pub fn ordered<'de>(self) -> impl DeserializeSeed<'de, Value = crate::ordered::OrderedSlab<T>>
+    where
+        T: Deserialize<'de>,
+    {
+        ordered::OrderedVisitor(self.limits, PhantomData)
+    }
+
+    /// Deserialize a [`NicheSlab`](crate::niche::NicheSlab) with these
+    /// limits.
+    pub fn niche<'de>(self) -> impl DeserializeSeed<'de, Value = crate::niche::NicheSlab<T>>
+    where
+        T: Deserialize<'de>,
+    {
+        niche::NicheVisitor(self.limits, PhantomData)
+    }
+}
+
+impl<T> Default for SlabSeed<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Clone for SlabSeed<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for SlabSeed<T> {}
+
+impl<T> fmt::Debug for SlabSeed<T> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("SlabSeed")
+            .field("max_key", &self.limits.max_key)
+            .field("max_len", &self.limits.max_len)
+            .field("max_capacity", &self.limits.max_capacity)
+            .finish()
+    }
+}
+
+impl<'de, T> DeserializeSeed<'de> for SlabSeed<T>
+where
+    T: Deserialize<'de>,
+{
+    type Value = Slab<T>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(SlabVisitor(self.limits, PhantomData))
+    }
+}
+
+struct MaxKey(usize);
+
+impl de::Expected for MaxKey {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(fmt, "a key no greater than {}", self.0)
+    }
+}
+
+struct MaxLen(usize);
+
+impl de::Expected for MaxLen {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(fmt, "at most {} values", self.0)
+    }
+}
+
+struct MaxCapacity(usize);
+
+impl de::Expected for MaxCapacity {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(fmt, "a capacity of at most {}", self.0)
+    }
+}
+
+struct KeyBelowCapacity(usize);
+
+impl de::Expected for KeyBelowCapacity {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(fmt, "a key below the maximum capacity of {}", self.0)
+    }
+}
+
+impl Limits {
+    pub(crate) const NONE: Self = Self {
+        max_key: usize::MAX,
+        max_len: usize::MAX,
+        max_capacity: usize::MAX,
+    };
+
+    // Return the capacity to preallocate for elements of type `T`
+    pub(crate) fn initial_capacity<T>(&self, size_hint: Option<usize>) -> usize {
+        cmp::min(
+            cautious_capacity::<T>(size_hint),
+            cmp::min(self.max_len, self.max_capacity),
+        )
+    }
+
+    pub(crate) fn check_key<E: de::Error>(&self, key: usize) -> Result<(), E> {
+        if key > self.max_key {
+            Err(E::invalid_value(
+                Unexpected::Unsigned(key as u64),
+                &MaxKey(self.max_key),
+            ))
+        } else if key >= self.max_capacity {
+            Err(E::invalid_value(
+                Unexpected::Unsigned(key as u64),
+                &KeyBelowCapacity(self.max_capacity),
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
+    pub(crate) fn check_len<E: de::Error>(&self, len: usize) -> Result<(), E> {
+        if len > self.max_len {
+            Err(E::invalid_length(len, &MaxLen(self.max_len)))
+        } else {
+            Ok(())
+        }
+    }
+
+    pub(crate) fn check_capacity<E: de::Error>(&self, capacity: usize) -> Result<(), E> {
+        if capacity > self.max_capacity {
+            Err(E::invalid_length(capacity, &MaxCapacity(self.max_capacity)))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+struct SlabVisitor<T>(Limits, PhantomData<T>);
 
 impl<'de, T> Visitor<'de> for SlabVisitor<T>
 where
@@ -39,10 +314,13 @@ where
     where
         A: MapAccess<'de>,
     {
-        let mut builder = Builder::with_capacity(map.size_hint().unwrap_or(0));
+        let limits = self.0;
+        let mut builder = SlabBuilder::with_capacity(limits.initial_capacity::<T>(map.size_hint()));
 
-        while let Some((key, value)) = map.next_entry()? {
-            builder.pair(key, value)
+        while let Some(key) = map.next_key()? {
+            limits.check_key(key)?;
+            builder.pair(key, map.next_value()?);
+            limits.check_len(builder.len())?;
         }
 
         Ok(builder.build())
@@ -57,6 +335,120 @@ where
     where
         D: Deserializer<'de>,
     {
-        deserializer.deserialize_map(SlabVisitor(PhantomData))
+        SlabSeed::new().deserialize(deserializer)
+    }
+
+    /// Deserialize into an existing slab, reusing its allocation.
+    ///
+    /// Values whose keys are present in both the slab and the input are
+    /// deserialized in place, values missing from the input are removed.
+    /// Afterwards the vacant list is rebuilt with the lowest keys first.
+    fn deserialize_in_place<D>(deserializer: D, place: &mut Self) -> Result<(), D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(InPlaceVisitor(place))
+    }
+}
+
+struct InPlaceVisitor<'a, T>(&'a mut Slab<T>);
+
+impl<'de, T> Visitor<'de> for InPlaceVisitor<'_, T>
+where
+    T: Deserialize<'de>,
+{
+    type Value = ();
+
+    fn expecting(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(fmt, "a map")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        // The vacant list is not maintained while entries are replaced, so it
+        // must be recreated afterwards, even if deserialization fails.
+        struct RepairGuard<'a, T> {
+            slab: &'a mut Slab<T>,
+        }
+        impl<T> Drop for RepairGuard<'_, T> {
+            fn drop(&mut self) {
+                let entries = &mut self.slab.entries;
+                while let Some(&Entry::Vacant(_)) = entries.last() {
+                    entries.pop();
+                }
+                self.slab.recreate_vacant_list();
+            }
+        }
+        let guard = RepairGuard { slab: self.0 };
+        let slab = &mut *guard.slab;
+
+        // Entries from `cursor` on still hold the previous contents. As long
+        // as the keys are sorted, which they are if the input was produced by
+        // `Slab::serialize`, values can be deserialized in place.
+        let mut cursor = 0;
+        while let Some(key) = map.next_key::<usize>()? {
+            if key >= cursor {
+                // Values between the previous key and this one are not in the
+                // input, so they are removed.
+                for stale in cursor..cmp::min(key, slab.entries.len()) {
+                    slab.vacate(stale);
+                }
+                cursor = key.saturating_add(1);
+
+                if let Some(Entry::Occupied(value)) = slab.entries.get_mut(key) {
+                    map.next_value_seed(InPlaceSeed(value))?;
+                    continue;
+                }
+            }
+
+            let value = map.next_value()?;
+            while slab.entries.len() < key {
+                slab.entries.push(Entry::Vacant(0));
+            }
+            if key == slab.entries.len() {
+                slab.entries.push(Entry::Occupied(value));
+                slab.len += 1;
+            } else {
+                // If an element with this key already exists, replace it.
+                if let Entry::Vacant(_) = slab.entries[key] {
+                    slab.len += 1;
+                }
+                slab.entries[key] = Entry::Occupied(value);
+            }
+        }
+
+        for stale in cursor..slab.entries.len() {
+            slab.vacate(stale);
+        }
+        Ok(())
+    }
+}
+
+impl<T> Slab<T> {
+    // Remove the value at `key` without updating the vacant list.
+    fn vacate(&mut self, key: usize) {
+        if let Entry::Occupied(_) = self.entries[key] {
+            let value = mem::replace(&mut self.entries[key], Entry::Vacant(0));
+            self.len -= 1;
+            drop(value);
+        }
+    }
+}
+
+struct InPlaceSeed<'a, T>(&'a mut T);
+
+impl<'de, T> DeserializeSeed<'de> for InPlaceSeed<'_, T>
+where
+    T: Deserialize<'de>,
+{
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        T::deserialize_in_place(deserializer, self.0)
     }
 }
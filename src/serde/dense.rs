@@ -0,0 +1,103 @@
+//! Serialize a `Slab` as a sequence of optional values.
+//!
+//! The default representation stores a key next to every value, which is
+//! wasteful for slabs with few vacant entries in non-self-describing formats
+//! such as bincode or postcard. This representation is a sequence with one
+//! `Option<T>` per entry, where the position in the sequence is the key.
+//!
+//! Like the default representation, it does not preserve the order of the
+//! vacant list or vacant entries after the last occupied one. Use
+//! [`exact`](super::exact) if those must be restored as well.
+//!
+//! Use it with `#[serde(with = "slab::repr::dense")]`.
+//!
+//! # Examples
+//!
+//! ```
+//! use serde::{Deserialize, Serialize};
+//! use slab::Slab;
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct World {
+//!     #[serde(with = "slab::repr::dense")]
+//!     entities: Slab<u32>,
+//! }
+//! ```
+
+use core::fmt;
+use core::marker::PhantomData;
+
+use serde::de::{DeserializeSeed, Deserializer, SeqAccess, Visitor};
+use serde::ser::Serializer;
+use serde::{Deserialize, Serialize};
+
+use super::{exact::Entries, Limits};
+use crate::{builder::SlabBuilder, Slab};
+
+/// Serialize a `Slab` as a sequence of `Option<T>`.
+pub fn serialize<T, S>(slab: &Slab<T>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Serialize,
+    S: Serializer,
+{
+    Entries(slab).serialize(serializer)
+}
+
+/// Deserialize a `Slab` from a sequence of `Option<T>`.
+///
+/// Use [`SlabSeed::dense`](crate::SlabSeed::dense) to limit the keys, length
+/// and capacity of the slab.
+pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Slab<T>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    DenseVisitor(Limits::NONE, PhantomData).deserialize(deserializer)
+}
+
+pub(super) struct DenseVisitor<T>(pub(super) Limits, pub(super) PhantomData<T>);
+
+impl<'de, T> DeserializeSeed<'de> for DenseVisitor<T>
+where
+    T: Deserialize<'de>,
+{
+    type Value = Slab<T>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de, T> Visitor<'de> for DenseVisitor<T>
+where
+    T: Deserialize<'de>,
+{
+    type Value = Slab<T>;
+
+    fn expecting(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(fmt, "a sequence of optional values")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let limits = self.0;
+        let mut builder = SlabBuilder::with_capacity(limits.initial_capacity::<T>(seq.size_hint()));
+
+        let mut key = 0;
+        while let Some(value) = seq.next_element()? {
+            if let Some(value) = value {
+                limits.check_key(key)?;
+                builder.pair(key, value);
+                limits.check_len(builder.len())?;
+            }
+            key += 1;
+        }
+
+        Ok(builder.build())
+    }
+}
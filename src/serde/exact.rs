@@ -0,0 +1,259 @@
+//! Serialize a `Slab` together with its exact layout.
+//!
+//! The default representation only contains the occupied entries, so a
+//! deserialized slab may hand out different keys than the original one did.
+//! This representation is a tuple of the capacity, every entry as an
+//! `Option<T>`, and the vacant keys in the order in which `insert` will reuse
+//! them. A slab restored from it returns the same keys as the serialized one.
+//!
+//! Use it with `#[serde(with = "slab::repr::exact")]`.
+//!
+//! # Examples
+//!
+//! ```
+//! use serde::{Deserialize, Serialize};
+//! use slab::Slab;
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Snapshot {
+//!     #[serde(with = "slab::repr::exact")]
+//!     connections: Slab<String>,
+//! }
+//! ```
+
+use core::fmt;
+use core::marker::PhantomData;
+
+use serde::de::{self, DeserializeSeed, Deserializer, SeqAccess, Unexpected, Visitor};
+use serde::ser::{SerializeSeq, SerializeTuple, Serializer};
+use serde::{Deserialize, Serialize};
+
+use super::Limits;
+use crate::{Entry, Slab};
+
+// Marks a vacant entry that has not been linked into the vacant list yet.
+const UNLINKED: usize = usize::MAX;
+
+/// Serialize a `Slab` including its capacity and vacant list.
+pub fn serialize<T, S>(slab: &Slab<T>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Serialize,
+    S: Serializer,
+{
+    let mut tuple = serializer.serialize_tuple(3)?;
+    tuple.serialize_element(&slab.capacity())?;
+    tuple.serialize_element(&Entries(slab))?;
+    tuple.serialize_element(&VacantKeys(slab))?;
+    tuple.end()
+}
+
+/// Deserialize a `Slab` that was serialized with [`serialize`].
+///
+/// The vacant keys are validated, so malformed input results in an error
+/// rather than a slab with a broken vacant list. The capacity is taken from
+/// the input, so use [`SlabSeed::exact`](crate::SlabSeed::exact) to limit it
+/// for untrusted input.
+pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Slab<T>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    ExactVisitor(Limits::NONE, PhantomData).deserialize(deserializer)
+}
+
+pub(super) struct Entries<'a, T>(pub(super) &'a Slab<T>);
+
+impl<T> Serialize for Entries<'_, T>
+where
+    T: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_seq(self.0.entries.iter().map(|entry| match entry {
+            Entry::Occupied(value) => Some(value),
+            Entry::Vacant(_) => None,
+        }))
+    }
+}
+
+struct VacantKeys<'a, T>(&'a Slab<T>);
+
+impl<T> Serialize for VacantKeys<'_, T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let slab = self.0;
+        let mut seq_serializer = serializer.serialize_seq(Some(slab.entries.len() - slab.len))?;
+        let mut key = slab.next;
+        while let Some(&Entry::Vacant(next)) = slab.entries.get(key) {
+            seq_serializer.serialize_element(&key)?;
+            key = next;
+        }
+        seq_serializer.end()
+    }
+}
+
+pub(super) struct ExactVisitor<T>(pub(super) Limits, pub(super) PhantomData<T>);
+
+impl<'de, T> DeserializeSeed<'de> for ExactVisitor<T>
+where
+    T: Deserialize<'de>,
+{
+    type Value = Slab<T>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_tuple(3, self)
+    }
+}
+
+impl<'de, T> Visitor<'de> for ExactVisitor<T>
+where
+    T: Deserialize<'de>,
+{
+    type Value = Slab<T>;
+
+    fn expecting(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(fmt, "a tuple of capacity, entries and vacant keys")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let limits = self.0;
+        let capacity: usize = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        limits.check_capacity(capacity)?;
+        let mut slab = seq
+            .next_element_seed(EntriesSeed(limits, PhantomData))?
+            .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+        seq.next_element_seed(VacantKeysSeed(&mut slab))?
+            .ok_or_else(|| de::Error::invalid_length(2, &self))?;
+
+        if let Some(additional) = capacity.checked_sub(slab.entries.len()) {
+            slab.entries
+                .try_reserve_exact(additional)
+                .map_err(|_| de::Error::custom("capacity overflow"))?;
+        }
+        Ok(slab)
+    }
+}
+
+struct EntriesSeed<T>(Limits, PhantomData<T>);
+
+impl<'de, T> DeserializeSeed<'de> for EntriesSeed<T>
+where
+    T: Deserialize<'de>,
+{
+    type Value = Slab<T>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de, T> Visitor<'de> for EntriesSeed<T>
+where
+    T: Deserialize<'de>,
+{
+    type Value = Slab<T>;
+
+    fn expecting(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(fmt, "a sequence of optional values")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let limits = self.0;
+        let mut slab = Slab::with_capacity(limits.initial_capacity::<T>(seq.size_hint()));
+
+        while let Some(value) = seq.next_element()? {
+            let key = slab.entries.len();
+            limits.check_capacity(key + 1)?;
+            match value {
+                Some(value) => {
+                    limits.check_key(key)?;
+                    slab.entries.push(Entry::Occupied(value));
+                    slab.len += 1;
+                    limits.check_len(slab.len)?;
+                }
+                None => slab.entries.push(Entry::Vacant(UNLINKED)),
+            }
+        }
+
+        Ok(slab)
+    }
+}
+
+struct VacantKeysSeed<'a, T>(&'a mut Slab<T>);
+
+impl<'de, T> DeserializeSeed<'de> for VacantKeysSeed<'_, T> {
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de, T> Visitor<'de> for VacantKeysSeed<'_, T> {
+    type Value = ();
+
+    fn expecting(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(fmt, "a sequence of vacant keys")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let slab = self.0;
+        let end = slab.entries.len();
+        let mut prev = None;
+        let mut linked = 0;
+
+        slab.next = end;
+        while let Some(key) = seq.next_element::<usize>()? {
+            // Only vacant entries that are not linked yet may be added, which
+            // also rules out cycles.
+            match slab.entries.get(key) {
+                Some(&Entry::Vacant(UNLINKED)) => {}
+                _ => {
+                    return Err(de::Error::invalid_value(
+                        Unexpected::Unsigned(key as u64),
+                        &"the key of an unlisted vacant entry",
+                    ))
+                }
+            }
+            match prev {
+                Some(prev) => slab.entries[prev] = Entry::Vacant(key),
+                None => slab.next = key,
+            }
+            slab.entries[key] = Entry::Vacant(end);
+            prev = Some(key);
+            linked += 1;
+        }
+
+        if linked != end - slab.len {
+            return Err(de::Error::invalid_length(
+                linked,
+                &"one key for every vacant entry",
+            ));
+        }
+        Ok(())
+    }
+}
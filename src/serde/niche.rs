@@ -0,0 +1,99 @@
+//! Serde support for `NicheSlab`.
+//!
+//! A `NicheSlab` uses the same representation as a `Slab`: a map from keys to
+//! values, in key order.
+
+use alloc::vec::Vec;
+use core::fmt;
+use core::marker::PhantomData;
+
+use serde::de::{Deserialize, DeserializeSeed, Deserializer, MapAccess, Visitor};
+use serde::ser::{Serialize, SerializeMap, Serializer};
+
+use super::Limits;
+use crate::niche::NicheSlab;
+
+impl<T> Serialize for NicheSlab<T>
+where
+    T: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map_serializer = serializer.serialize_map(Some(self.len()))?;
+        for (key, value) in self {
+            map_serializer.serialize_key(&key)?;
+            map_serializer.serialize_value(value)?;
+        }
+        map_serializer.end()
+    }
+}
+
+impl<'de, T> Deserialize<'de> for NicheSlab<T>
+where
+    T: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        NicheVisitor(Limits::NONE, PhantomData).deserialize(deserializer)
+    }
+}
+
+pub(super) struct NicheVisitor<T>(pub(super) Limits, pub(super) PhantomData<T>);
+
+impl<'de, T> DeserializeSeed<'de> for NicheVisitor<T>
+where
+    T: Deserialize<'de>,
+{
+    type Value = NicheSlab<T>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de, T> Visitor<'de> for NicheVisitor<T>
+where
+    T: Deserialize<'de>,
+{
+    type Value = NicheSlab<T>;
+
+    fn expecting(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(fmt, "a map")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let limits = self.0;
+        let mut entries = Vec::with_capacity(limits.initial_capacity::<T>(map.size_hint()));
+        let mut len = 0;
+        while let Some(key) = map.next_key::<usize>()? {
+            limits.check_key(key)?;
+            let value = map.next_value()?;
+            while entries.len() <= key {
+                entries.push(None);
+            }
+            // If an element with this key already exists, replace it, like
+            // `Slab` does
+            if entries[key].replace(value).is_none() {
+                len += 1;
+                limits.check_len(len)?;
+            }
+        }
+
+        // Reuse the lowest vacant keys first, like `Slab` does
+        let free = (0..entries.len())
+            .rev()
+            .filter(|&key| entries[key].is_none())
+            .collect();
+        Ok(NicheSlab { entries, free })
+    }
+}
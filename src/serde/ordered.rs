@@ -0,0 +1,107 @@
+//! Serde support for `OrderedSlab`.
+//!
+//! An `OrderedSlab` is serialized as a map from keys to values in insertion
+//! order, and deserializing it restores that order from the order of the map.
+
+use alloc::vec::Vec;
+use core::fmt;
+use core::marker::PhantomData;
+
+use serde::de::{self, Deserialize, DeserializeSeed, Deserializer, MapAccess, Visitor};
+use serde::ser::{Serialize, SerializeMap, Serializer};
+
+use super::Limits;
+use crate::linked::{Node, SlabList};
+use crate::ordered::OrderedSlab;
+use crate::{DuplicateKeys, SlabBuilder};
+
+impl<T> Serialize for OrderedSlab<T>
+where
+    T: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map_serializer = serializer.serialize_map(Some(self.len()))?;
+        for (key, value) in self {
+            map_serializer.serialize_key(&key)?;
+            map_serializer.serialize_value(value)?;
+        }
+        map_serializer.end()
+    }
+}
+
+impl<'de, T> Deserialize<'de> for OrderedSlab<T>
+where
+    T: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        OrderedVisitor(Limits::NONE, PhantomData).deserialize(deserializer)
+    }
+}
+
+pub(super) struct OrderedVisitor<T>(pub(super) Limits, pub(super) PhantomData<T>);
+
+impl<'de, T> DeserializeSeed<'de> for OrderedVisitor<T>
+where
+    T: Deserialize<'de>,
+{
+    type Value = OrderedSlab<T>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de, T> Visitor<'de> for OrderedVisitor<T>
+where
+    T: Deserialize<'de>,
+{
+    type Value = OrderedSlab<T>;
+
+    fn expecting(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(fmt, "a map")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let limits = self.0;
+        let capacity = limits.initial_capacity::<Node<T>>(map.size_hint());
+        let mut builder = SlabBuilder::with_capacity(capacity).duplicate_keys(DuplicateKeys::Error);
+        let mut order = Vec::with_capacity(capacity);
+
+        // Unlike for `Slab`, a duplicate key cannot simply replace the
+        // previous value, because the key would be in two places in the
+        // order.
+        while let Some(key) = map.next_key()? {
+            limits.check_key(key)?;
+            let node = Node {
+                value: map.next_value()?,
+                prev: None,
+                next: None,
+            };
+            if builder.insert_at(key, node).is_err() {
+                return Err(de::Error::custom(format_args!("duplicate key {}", key)));
+            }
+            order.push(key);
+            limits.check_len(order.len())?;
+        }
+
+        let mut nodes = builder.build();
+        for pair in order.windows(2) {
+            nodes[pair[0]].next = Some(pair[1]);
+            nodes[pair[1]].prev = Some(pair[0]);
+        }
+        let list = SlabList::from_parts(nodes, order.first().copied(), order.last().copied());
+        Ok(OrderedSlab { list })
+    }
+}
@@ -0,0 +1,351 @@
+//! A slab split into shards that are owned by threads.
+//!
+//! Every thread is assigned one of the shards and inserts values into it. The
+//! index of the shard is stored in the high bits of the key, so values can be
+//! found again from any thread. Since each shard is a separate [`Slab`] with
+//! its own vacant list, threads inserting values don't contend with each
+//! other as long as there are at least as many shards as threads.
+//!
+//! Removing a value from a thread that doesn't own its shard would contend
+//! with the owner, so such removals are deferred: the key is pushed onto the
+//! shard's remote free list, and the value is dropped the next time any
+//! thread locks the shard, be it to insert, access or count values. A removed
+//! value is therefore never observed again, but it may live until then. Use
+//! [`ShardedSlab::take`] to remove a value immediately from any thread
+//! instead.
+//!
+//! Each shard is guarded by a mutex, which is not reentrant. Values are
+//! therefore only accessed inside a closure passed to [`ShardedSlab::with`],
+//! and the closure must not use the slab itself. Accessing a value locks its
+//! shard, so readers do contend with the owning thread and with each other;
+//! only insertions into different shards are independent.
+//!
+//! # Examples
+//!
+//! ```
+//! use slab::shard::ShardedSlab;
+//! use std::thread;
+//!
+//! let slab = ShardedSlab::with_shards(4);
+//!
+//! let keys: Vec<usize> = thread::scope(|scope| {
+//!     let handles: Vec<_> = (0..4)
+//!         .map(|i| {
+//!             let slab = &slab;
+//!             scope.spawn(move || slab.insert(i))
+//!         })
+//!         .collect();
+//!     handles.into_iter().map(|h| h.join().unwrap()).collect()
+//! });
+//!
+//! for (i, &key) in keys.iter().enumerate() {
+//!     assert_eq!(slab.with(key, |&value| value), Some(i));
+//! }
+//! ```
+
+use crate::list::{ListStorage, MutRefListStorage, VecStorage};
+use crate::Slab;
+
+use std::cell::Cell;
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+/// A slab split into per-thread shards.
+///
+/// See the [module documentation] for more details.
+///
+/// [module documentation]: index.html
+pub struct ShardedSlab<T, L: ListStorage = VecStorage> {
+    shards: Box<[Shard<T, L>]>,
+
+    // Number of low key bits that hold the key within a shard
+    local_bits: u32,
+}
+
+struct Shard<T, L: ListStorage> {
+    slab: Mutex<Slab<T, L>>,
+
+    // Keys removed by threads that don't own this shard
+    remote_free: Mutex<Vec<usize>>,
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    // Slab operations leave the slab consistent even if they panic
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+// Index of the current thread, used to pick its shard
+fn thread_index() -> usize {
+    static NEXT_THREAD_INDEX: AtomicUsize = AtomicUsize::new(0);
+    thread_local! {
+        static THREAD_INDEX: Cell<Option<usize>> = const { Cell::new(None) };
+    }
+
+    THREAD_INDEX.with(|index| match index.get() {
+        Some(index) => index,
+        None => {
+            let new_index = NEXT_THREAD_INDEX.fetch_add(1, Ordering::Relaxed);
+            index.set(Some(new_index));
+            new_index
+        }
+    })
+}
+
+impl<T> ShardedSlab<T> {
+    /// Construct a new, empty `ShardedSlab` with the given number of shards.
+    ///
+    /// Threads are assigned to shards round-robin in the order in which they
+    /// first use any `ShardedSlab`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `shards` is 0.
+    pub fn with_shards(shards: usize) -> Self {
+        Self::with_shards_from(shards, Slab::new)
+    }
+}
+
+impl<T, L: ListStorage> ShardedSlab<T, L> {
+    /// Construct a new `ShardedSlab` with the given number of shards, each
+    /// of which is created by calling `f`.
+    ///
+    /// This allows using a different [`ListStorage`] for the shards, or
+    /// reserving capacity up front.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `shards` is 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use slab::shard::ShardedSlab;
+    /// use slab::Slab;
+    ///
+    /// let slab = ShardedSlab::<u64>::with_shards_from(8, || Slab::with_capacity(1024));
+    /// assert_eq!(slab.shards(), 8);
+    /// ```
+    pub fn with_shards_from<F>(shards: usize, mut f: F) -> Self
+    where
+        F: FnMut() -> Slab<T, L>,
+    {
+        assert!(shards > 0, "a sharded slab needs at least one shard");
+        let shard_bits = shards.next_power_of_two().trailing_zeros();
+
+        Self {
+            shards: (0..shards)
+                .map(|_| Shard {
+                    slab: Mutex::new(f()),
+                    remote_free: Mutex::new(Vec::new()),
+                })
+                .collect(),
+            local_bits: usize::BITS - shard_bits,
+        }
+    }
+
+    /// Return the number of shards.
+    pub fn shards(&self) -> usize {
+        self.shards.len()
+    }
+
+    /// Return the number of stored values.
+    ///
+    /// Every shard is locked in turn and its deferred removals are processed,
+    /// so removed values are not counted. The result may still be outdated if
+    /// other threads modify the slab concurrently.
+    pub fn len(&self) -> usize
+    where
+        L: MutRefListStorage,
+    {
+        (0..self.shards.len())
+            .map(|index| self.lock_shard(index).len())
+            .sum()
+    }
+
+    /// Return `true` if there are no values stored in the slab.
+    pub fn is_empty(&self) -> bool
+    where
+        L: MutRefListStorage,
+    {
+        self.len() == 0
+    }
+
+    /// Insert a value into the current thread's shard, returning the key
+    /// assigned to the value.
+    ///
+    /// Deferred removals from other threads are processed first.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key within the shard doesn't fit in the bits left over
+    /// by the shard index.
+    pub fn insert(&self, val: T) -> usize
+    where
+        L: MutRefListStorage,
+    {
+        let index = self.local_shard();
+        let mut slab = self.lock_shard(index);
+
+        let local_key = slab.vacant_key();
+        assert!(
+            local_key.checked_shr(self.local_bits).unwrap_or(0) == 0,
+            "capacity overflow"
+        );
+        slab.insert(val);
+        self.key(index, local_key)
+    }
+
+    /// Call `f` with a reference to the value associated with the given key
+    /// and return its result.
+    ///
+    /// If the given key is not associated with a value, then `f` is not
+    /// called and `None` is returned.
+    ///
+    /// The value's shard is locked while `f` runs, and deferred removals from
+    /// that shard are processed first. `f` must not call any
+    /// method of this slab: accessing the same shard again deadlocks or
+    /// panics, and locking another shard can deadlock with a thread doing
+    /// the same the other way around.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use slab::shard::ShardedSlab;
+    ///
+    /// let slab = ShardedSlab::with_shards(2);
+    /// let key = slab.insert(String::from("hello"));
+    ///
+    /// assert_eq!(slab.with(key, |value| value.len()), Some(5));
+    /// assert_eq!(slab.with(key + 1, |value| value.len()), None);
+    /// ```
+    pub fn with<F, R>(&self, key: usize, f: F) -> Option<R>
+    where
+        F: FnOnce(&T) -> R,
+        L: MutRefListStorage,
+    {
+        let (index, local_key) = self.split_key(key);
+        if index >= self.shards.len() {
+            return None;
+        }
+        self.lock_shard(index).get(local_key).map(f)
+    }
+
+    /// Return `true` if a value is associated with the given key.
+    ///
+    /// Returns `false` once the value has been removed, even if the removal
+    /// was deferred.
+    pub fn contains(&self, key: usize) -> bool
+    where
+        L: MutRefListStorage,
+    {
+        self.with(key, |_| ()).is_some()
+    }
+
+    /// Remove the value associated with the given key.
+    ///
+    /// If the key belongs to the current thread's shard, the value is dropped
+    /// immediately. Otherwise the removal is deferred until the shard is next
+    /// locked by any method of this slab, which processes it before looking
+    /// at the shard's values. Keys that are not associated with a value are
+    /// ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use slab::shard::ShardedSlab;
+    /// use std::thread;
+    ///
+    /// let slab = ShardedSlab::with_shards(2);
+    /// let key = slab.insert("hello");
+    ///
+    /// thread::scope(|scope| {
+    ///     scope.spawn(|| slab.remove(key));
+    /// });
+    ///
+    /// assert!(!slab.contains(key));
+    /// assert!(slab.is_empty());
+    /// ```
+    pub fn remove(&self, key: usize)
+    where
+        L: MutRefListStorage,
+    {
+        let (index, local_key) = self.split_key(key);
+        let shard = match self.shards.get(index) {
+            Some(shard) => shard,
+            None => return,
+        };
+
+        if index == self.local_shard() {
+            self.lock_shard(index).try_remove(local_key);
+        } else {
+            lock(&shard.remote_free).push(local_key);
+        }
+    }
+
+    /// Remove and return the value associated with the given key immediately,
+    /// regardless of which thread owns its shard.
+    ///
+    /// If the given key is not associated with a value, then `None` is
+    /// returned.
+    pub fn take(&self, key: usize) -> Option<T>
+    where
+        L: MutRefListStorage,
+    {
+        let (index, local_key) = self.split_key(key);
+        if index >= self.shards.len() {
+            return None;
+        }
+        self.lock_shard(index).try_remove(local_key)
+    }
+
+    /// Process the deferred removals of all shards.
+    pub fn flush(&self)
+    where
+        L: MutRefListStorage,
+    {
+        for index in 0..self.shards.len() {
+            drop(self.lock_shard(index));
+        }
+    }
+
+    // Lock a shard and process its deferred removals, so that values removed
+    // by other threads are never observed again
+    fn lock_shard(&self, index: usize) -> MutexGuard<'_, Slab<T, L>>
+    where
+        L: MutRefListStorage,
+    {
+        let shard = &self.shards[index];
+        let mut slab = lock(&shard.slab);
+        let keys = std::mem::take(&mut *lock(&shard.remote_free));
+        for local_key in keys {
+            slab.try_remove(local_key);
+        }
+        slab
+    }
+
+    fn local_shard(&self) -> usize {
+        thread_index() % self.shards.len()
+    }
+
+    fn key(&self, index: usize, local_key: usize) -> usize {
+        index.checked_shl(self.local_bits).unwrap_or(0) | local_key
+    }
+
+    fn split_key(&self, key: usize) -> (usize, usize) {
+        let index = key.checked_shr(self.local_bits).unwrap_or(0);
+        let local_key = key
+            & usize::MAX
+                .checked_shr(usize::BITS - self.local_bits)
+                .unwrap_or(0);
+        (index, local_key)
+    }
+}
+
+impl<T, L: ListStorage> fmt::Debug for ShardedSlab<T, L> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("ShardedSlab")
+            .field("shards", &self.shards.len())
+            .finish()
+    }
+}
@@ -0,0 +1,389 @@
+//! Support for struct-of-arrays slabs generated by `#[derive(SlabSoA)]`.
+//!
+//! A `Slab<T>` stores each value next to its entry tag, so iterating over a
+//! single field of `T` still loads every other field into the cache. With the
+//! `derive` feature enabled, `#[derive(SlabSoA)]` on a struct `Foo` with named
+//! fields generates a `FooSlab` type that stores every field in a separate
+//! [`Column`] instead. Occupancy is tracked once for all columns by a
+//! `Slab<()>`, so keys are handed out and reused exactly like they are by
+//! `Slab`.
+//!
+//! The generated type has the following API:
+//!
+//! * `new`, `with_capacity`, `capacity`, `len`, `is_empty`, `contains` and
+//!   `clear`, like `Slab`.
+//! * `insert(Foo) -> usize`, `try_remove(usize) -> Option<Foo>` and
+//!   `remove(usize) -> Foo`.
+//! * `get` and `get_mut`, returning a `FooRef` or `FooMut` with one reference
+//!   per field, and `iter` over all occupied keys and their `FooRef`s.
+//! * `iter_<field>` and `iter_<field>_mut` for every field, returning a
+//!   [`ColumnIter`] or [`ColumnIterMut`] over that column only.
+//! * `columns_mut`, returning a `FooColumnsMut` with a [`ColumnIterMut`] for
+//!   every field, to update several columns in one pass.
+//!
+//! The generated code refers to this crate as `::slab`. If it is available
+//! under another path, for example because the dependency was renamed, set
+//! that path with `#[slab(crate = "path")]` on the struct.
+//!
+//! Structs with generic parameters are not supported, and neither are fields
+//! whose methods would clash, such as `pos` and `pos_mut`, which would both
+//! get an `iter_pos_mut`:
+//!
+#![cfg_attr(feature = "derive", doc = "```compile_fail")]
+#![cfg_attr(not(feature = "derive"), doc = "```ignore")]
+//! use slab::SlabSoA;
+//!
+//! #[derive(SlabSoA)]
+//! struct Particle {
+//!     pos: f32,
+//!     pos_mut: bool,
+//! }
+//! ```
+//!
+//! Every column stores its values in a [`ListStorage`], [`VecStorage`] by
+//! default. Another storage can be chosen per field with
+//! `#[slab(storage = "path")]`. It has to implement [`CapacityListStorage`],
+//! so that the column reserves capacity along with the keys,
+//! [`MutRefListStorage`] for `get_mut`, and [`AsSliceListStorage`] for
+//! [`ColumnIterMut`].
+//!
+//! # Examples
+//!
+#![cfg_attr(feature = "derive", doc = "```")]
+#![cfg_attr(not(feature = "derive"), doc = "```ignore")]
+//! use slab::SlabSoA;
+//!
+//! #[derive(SlabSoA)]
+//! struct Particle {
+//!     pos: f32,
+//!     vel: f32,
+//! }
+//!
+//! let mut particles = ParticleSlab::new();
+//! let a = particles.insert(Particle { pos: 0.0, vel: 1.0 });
+//! let b = particles.insert(Particle { pos: 5.0, vel: -2.0 });
+//!
+//! let columns = particles.columns_mut();
+//! for ((_, pos), (_, vel)) in columns.pos.zip(columns.vel) {
+//!     *pos += *vel;
+//! }
+//!
+//! let positions: Vec<_> = particles.iter_pos().map(|(_, &pos)| pos).collect();
+//! assert_eq!(positions, [1.0, 3.0]);
+//!
+//! let removed = particles.remove(a);
+//! assert_eq!(removed.pos, 1.0);
+//! assert_eq!(*particles.get(b).unwrap().vel, -2.0);
+//! ```
+
+use alloc::vec::Vec;
+use core::iter::FusedIterator;
+use core::mem::MaybeUninit;
+use core::{fmt, slice};
+
+use crate::list::{
+    AsSliceListStorage, CapacityListStorage, ItemMut, List, ListStorage, MutRefListStorage,
+    VecStorage,
+};
+use crate::Slab;
+
+/// The keys and columns of a generated struct-of-arrays slab.
+///
+/// Its fields are private so that code next to the generated slab can only
+/// break the invariant that every occupied key is initialized in every column
+/// by using `unsafe`.
+pub struct Storage<C> {
+    keys: Slab<()>,
+    columns: C,
+}
+
+impl<C> Storage<C> {
+    #[doc(hidden)]
+    pub fn new(capacity: usize, columns: C) -> Self {
+        Self {
+            keys: Slab::with_capacity(capacity),
+            columns,
+        }
+    }
+
+    #[doc(hidden)]
+    pub fn keys(&self) -> &Slab<()> {
+        &self.keys
+    }
+
+    #[doc(hidden)]
+    pub fn columns(&self) -> &C {
+        &self.columns
+    }
+
+    /// # Safety
+    ///
+    /// Every key that is occupied in the returned slab must be initialized
+    /// in every column, and columns must not be replaced.
+    #[doc(hidden)]
+    pub unsafe fn parts_mut(&mut self) -> (&mut Slab<()>, &mut C) {
+        (&mut self.keys, &mut self.columns)
+    }
+}
+
+impl<C> fmt::Debug for Storage<C> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("Storage")
+            .field("len", &self.keys.len())
+            .finish()
+    }
+}
+
+/// The storage of one field in a generated struct-of-arrays slab, and of the
+/// values of an [`InPlaceSlab`](crate::inplace::InPlaceSlab).
+///
+/// A column does not know which of its slots are occupied, so it never drops
+/// its values. The slab using it is responsible for that.
+pub struct Column<T, L: ListStorage = VecStorage> {
+    values: L::List<MaybeUninit<T>>,
+}
+
+impl<T, L: ListStorage> Column<T, L> {
+    #[doc(hidden)]
+    pub fn new() -> Self
+    where
+        L: CapacityListStorage,
+    {
+        Self::with_capacity(0)
+    }
+
+    #[doc(hidden)]
+    pub fn with_capacity(capacity: usize) -> Self
+    where
+        L: CapacityListStorage,
+    {
+        Self {
+            values: L::with_capacity(capacity),
+        }
+    }
+
+    #[doc(hidden)]
+    pub fn reserve(&mut self, additional: usize)
+    where
+        L: CapacityListStorage,
+    {
+        L::reserve(&mut self.values, additional);
+    }
+
+    /// Return the number of values the column can store without
+    /// reallocating.
+    pub fn capacity(&self) -> usize
+    where
+        L: CapacityListStorage,
+    {
+        L::capacity(&self.values)
+    }
+
+    // Store a value in a vacant slot. Keys are handed out in order, so `key`
+    // is at most one past the last slot.
+    #[doc(hidden)]
+    pub fn write(&mut self, key: usize, value: T) {
+        if key == self.values.len() {
+            self.values.push(MaybeUninit::new(value));
+        } else {
+            self.values
+                .get_mut(key)
+                .expect("invalid key")
+                .set(MaybeUninit::new(value));
+        }
+    }
+
+    // Return the slot at `key` to initialize a value in place, growing the
+    // column with uninitialized slots if needed.
+    #[doc(hidden)]
+    pub fn slot(&mut self, key: usize) -> &mut MaybeUninit<T>
+    where
+        L: MutRefListStorage,
+    {
+        while key >= self.values.len() {
+            self.values.push(MaybeUninit::uninit());
+        }
+        L::into_mut_ref(self.values.get_mut(key).expect("invalid key"))
+    }
+
+    /// # Safety
+    ///
+    /// The slot at `key` must be occupied. It is vacant afterwards.
+    #[doc(hidden)]
+    pub unsafe fn take(&mut self, key: usize) -> T {
+        self.values
+            .get(key)
+            .expect("invalid key")
+            .assume_init_read()
+    }
+
+    /// # Safety
+    ///
+    /// The slot at `key` must be occupied.
+    #[doc(hidden)]
+    pub unsafe fn get(&self, key: usize) -> &T {
+        self.values.get(key).expect("invalid key").assume_init_ref()
+    }
+
+    /// # Safety
+    ///
+    /// The slot at `key` must be occupied.
+    #[doc(hidden)]
+    pub unsafe fn get_mut(&mut self, key: usize) -> &mut T
+    where
+        L: MutRefListStorage,
+    {
+        L::into_mut_ref(self.values.get_mut(key).expect("invalid key")).assume_init_mut()
+    }
+}
+
+impl<T> Column<T> {
+    // Like `new`, but usable in constant expressions
+    #[doc(hidden)]
+    pub const fn new_vec() -> Self {
+        Self { values: Vec::new() }
+    }
+}
+
+impl<T, L: CapacityListStorage> Default for Column<T, L> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, L: CapacityListStorage> fmt::Debug for Column<T, L> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("Column")
+            .field("cap", &self.capacity())
+            .finish()
+    }
+}
+
+/// An iterator over the values of one column and their keys.
+pub struct ColumnIter<'a, T, L: ListStorage = VecStorage> {
+    keys: crate::Iter<'a, ()>,
+    column: &'a Column<T, L>,
+}
+
+impl<'a, T, L: ListStorage> ColumnIter<'a, T, L> {
+    /// # Safety
+    ///
+    /// Every key returned by `keys` must be occupied in `column`.
+    #[doc(hidden)]
+    pub unsafe fn new(keys: crate::Iter<'a, ()>, column: &'a Column<T, L>) -> Self {
+        Self { keys, column }
+    }
+}
+
+impl<'a, T, L: ListStorage> Iterator for ColumnIter<'a, T, L> {
+    type Item = (usize, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (key, ()) = self.keys.next()?;
+        // Safety: guaranteed by the caller of `new`
+        Some((key, unsafe { self.column.get(key) }))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.keys.size_hint()
+    }
+}
+
+impl<T, L: ListStorage> DoubleEndedIterator for ColumnIter<'_, T, L> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let (key, ()) = self.keys.next_back()?;
+        // Safety: guaranteed by the caller of `new`
+        Some((key, unsafe { self.column.get(key) }))
+    }
+}
+
+impl<T, L: ListStorage> ExactSizeIterator for ColumnIter<'_, T, L> {}
+
+impl<T, L: ListStorage> FusedIterator for ColumnIter<'_, T, L> {}
+
+impl<T, L: ListStorage> Clone for ColumnIter<'_, T, L> {
+    fn clone(&self) -> Self {
+        Self {
+            keys: self.keys.clone(),
+            column: self.column,
+        }
+    }
+}
+
+impl<T, L: ListStorage> fmt::Debug for ColumnIter<'_, T, L> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("ColumnIter")
+            .field("remaining", &self.keys.len())
+            .finish()
+    }
+}
+
+/// A mutable iterator over the values of one column and their keys.
+pub struct ColumnIterMut<'a, T> {
+    keys: crate::Iter<'a, ()>,
+    values: slice::IterMut<'a, MaybeUninit<T>>,
+
+    // Key of the next slot returned by `values` from the front and one past
+    // the key of the next slot returned from the back
+    next_key: usize,
+    end_key: usize,
+}
+
+impl<'a, T> ColumnIterMut<'a, T> {
+    /// # Safety
+    ///
+    /// Every key returned by `keys` must be occupied in `column`.
+    #[doc(hidden)]
+    pub unsafe fn new<L>(keys: crate::Iter<'a, ()>, column: &'a mut Column<T, L>) -> Self
+    where
+        L: AsSliceListStorage,
+    {
+        let values = L::as_mut_slice(&mut column.values);
+        Self {
+            keys,
+            end_key: values.len(),
+            values: values.iter_mut(),
+            next_key: 0,
+        }
+    }
+}
+
+impl<'a, T> Iterator for ColumnIterMut<'a, T> {
+    type Item = (usize, &'a mut T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (key, ()) = self.keys.next()?;
+        // Keys are returned in ascending order, so skip the vacant slots
+        // in between.
+        let value = self.values.nth(key - self.next_key)?;
+        self.next_key = key + 1;
+        // Safety: guaranteed by the caller of `new`
+        Some((key, unsafe { value.assume_init_mut() }))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.keys.size_hint()
+    }
+}
+
+impl<T> DoubleEndedIterator for ColumnIterMut<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let (key, ()) = self.keys.next_back()?;
+        let value = self.values.nth_back(self.end_key - key - 1)?;
+        self.end_key = key;
+        // Safety: guaranteed by the caller of `new`
+        Some((key, unsafe { value.assume_init_mut() }))
+    }
+}
+
+impl<T> ExactSizeIterator for ColumnIterMut<'_, T> {}
+
+impl<T> FusedIterator for ColumnIterMut<'_, T> {}
+
+impl<T> fmt::Debug for ColumnIterMut<'_, T> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("ColumnIterMut")
+            .field("remaining", &self.keys.len())
+            .finish()
+    }
+}
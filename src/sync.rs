@@ -0,0 +1,490 @@
+//! A slab that can be shared between threads.
+//!
+//! [`Slab`](crate::Slab) needs `&mut self` to insert and remove values, so
+//! sharing one between threads requires a lock around it. The slab in this
+//! module supports inserting, accessing and removing values through `&self`
+//! instead, without any locks.
+//!
+//! Like the single-threaded slab, every slot is either occupied or vacant, and
+//! vacant slots form a stack linked through their `next` fields. Here the
+//! stack is a lock-free Treiber stack whose head is tagged with a counter to
+//! avoid the ABA problem. Unlike the single-threaded slab, the capacity is
+//! fixed when the slab is created.
+//!
+//! The tag wraps around after `2^b` updates of the head, where `b` is the
+//! number of bits of a `usize` left over by the index of the slots: at least
+//! half of them, and more for smaller capacities. A thread that is suspended
+//! while popping a slot, for exactly a multiple of `2^b` updates by other
+//! threads that leave the same slot on top, installs a stale head. On 64-bit
+//! targets this takes at least 2^32 updates, but on 32-bit targets a slab with
+//! a capacity of 65,535 values already wraps after 65,536 updates. A popped
+//! slot is therefore only used after it has been claimed by switching its
+//! state from vacant, so a stale head can at worst make the slab skip vacant
+//! slots, but never hands the same slot to two threads.
+//!
+//! # Examples
+//!
+//! ```
+//! use slab::sync::Slab;
+//! use std::thread;
+//!
+//! let slab = Slab::with_capacity(16);
+//!
+//! thread::scope(|scope| {
+//!     for i in 0..4 {
+//!         let slab = &slab;
+//!         scope.spawn(move || {
+//!             let key = slab.insert(i).unwrap();
+//!             assert_eq!(*slab.get(key).unwrap(), i);
+//!             assert!(slab.remove(key));
+//!         });
+//!     }
+//! });
+//!
+//! assert!(slab.is_empty());
+//! ```
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::cell::UnsafeCell;
+use core::mem::MaybeUninit;
+use core::sync::atomic::{AtomicUsize, Ordering};
+use core::{fmt, ops, ptr};
+
+// The head of the vacant stack stores the index of the top slot in the low
+// bits that are needed to store the capacity, and a tag that is incremented
+// on every update in the remaining high bits. The index may use at most half
+// of the bits.
+const MAX_CAPACITY: usize = usize::MAX >> (usize::BITS / 2);
+
+// Slot states. The bits above the flags count the outstanding `Ref`s.
+const VACANT: usize = 0;
+const OCCUPIED: usize = 0b01;
+const REMOVED: usize = 0b10;
+const REF: usize = 0b100;
+
+// State of a popped slot whose value is being written. It is never reached
+// by an occupied slot, which keeps `OCCUPIED` set until it is vacant again.
+const CLAIMED: usize = REMOVED;
+
+/// Pre-allocated storage for a uniform data type that can be shared between
+/// threads.
+///
+/// See the [module documentation] for more details.
+///
+/// [module documentation]: index.html
+pub struct Slab<T> {
+    slots: Box<[Slot<T>]>,
+
+    // Tagged index of the first vacant slot. The index is set to the slab's
+    // capacity when the slab is full.
+    head: AtomicUsize,
+    index_mask: usize,
+
+    // Number of filled slots
+    len: AtomicUsize,
+}
+
+// Slots don't reuse the `Entry` enum of the single-threaded slab, because a
+// thread popping a vacant slot reads its `next` field while another thread
+// that popped the same slot first may already be writing a value into it.
+// `next` is therefore an atomic beside the value instead of sharing its
+// memory, and the state is kept in an atomic of its own so that `Ref`s can
+// be counted next to it.
+struct Slot<T> {
+    state: AtomicUsize,
+
+    // Index of the next vacant slot while this one is vacant
+    next: AtomicUsize,
+
+    // Initialized while the state is `OCCUPIED`
+    value: UnsafeCell<MaybeUninit<T>>,
+}
+
+/// A reference to a value stored in a [`Slab`].
+///
+/// While a `Ref` exists, the value is kept alive even if it is removed from
+/// the slab; it is dropped once the last `Ref` to it is dropped.
+pub struct Ref<'a, T> {
+    slab: &'a Slab<T>,
+    key: usize,
+}
+
+unsafe impl<T: Send> Send for Slab<T> {}
+unsafe impl<T: Send + Sync> Sync for Slab<T> {}
+
+fn tagged(index_mask: usize, prev_head: usize, index: usize) -> usize {
+    (prev_head & !index_mask).wrapping_add(index_mask + 1) | index
+}
+
+impl<T> Slab<T> {
+    /// Construct a new, empty `Slab` that can store up to `capacity` values.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` does not fit in half the bits of a `usize`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use slab::sync::Slab;
+    ///
+    /// let slab = Slab::<i32>::with_capacity(10);
+    /// assert_eq!(slab.capacity(), 10);
+    /// ```
+    pub fn with_capacity(capacity: usize) -> Self {
+        assert!(capacity <= MAX_CAPACITY, "capacity overflow");
+        let index_bits = usize::BITS - capacity.leading_zeros();
+
+        let slots: Vec<Slot<T>> = (0..capacity)
+            .map(|key| Slot {
+                state: AtomicUsize::new(VACANT),
+                next: AtomicUsize::new(key + 1),
+                value: UnsafeCell::new(MaybeUninit::uninit()),
+            })
+            .collect();
+
+        Self {
+            slots: slots.into_boxed_slice(),
+            head: AtomicUsize::new(0),
+            index_mask: (1 << index_bits) - 1,
+            len: AtomicUsize::new(0),
+        }
+    }
+
+    /// Return the number of values the slab can store.
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Return the number of stored values.
+    ///
+    /// Values that were removed are not counted, even while a [`Ref`] to
+    /// them still exists.
+    pub fn len(&self) -> usize {
+        self.len.load(Ordering::Relaxed)
+    }
+
+    /// Return `true` if there are no values stored in the slab.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Insert a value in the slab, returning the key assigned to the value.
+    ///
+    /// If the slab is full, the value is returned back as the error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use slab::sync::Slab;
+    ///
+    /// let slab = Slab::with_capacity(1);
+    /// let key = slab.insert("hello").unwrap();
+    /// assert_eq!(*slab.get(key).unwrap(), "hello");
+    /// assert_eq!(slab.insert("world"), Err("world"));
+    /// ```
+    pub fn insert(&self, val: T) -> Result<usize, T> {
+        let key = match self.pop_vacant() {
+            Some(key) => key,
+            None => return Err(val),
+        };
+
+        let slot = &self.slots[key];
+        // Claiming the slot gave us exclusive access
+        unsafe {
+            (*slot.value.get()).write(val);
+        }
+        self.len.fetch_add(1, Ordering::Relaxed);
+        slot.state.store(OCCUPIED, Ordering::Release);
+        Ok(key)
+    }
+
+    /// Return a reference to the value associated with the given key.
+    ///
+    /// If the given key is not associated with a value, or the value was
+    /// removed, then `None` is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use slab::sync::Slab;
+    ///
+    /// let slab = Slab::with_capacity(4);
+    /// let key = slab.insert(String::from("hello")).unwrap();
+    ///
+    /// assert_eq!(slab.get(key).unwrap().as_str(), "hello");
+    /// assert!(slab.get(123).is_none());
+    /// ```
+    pub fn get(&self, key: usize) -> Option<Ref<'_, T>> {
+        let slot = self.slots.get(key)?;
+        let mut state = slot.state.load(Ordering::Acquire);
+        loop {
+            if state & (OCCUPIED | REMOVED) != OCCUPIED {
+                return None;
+            }
+            let new_state = state.checked_add(REF).expect("too many references");
+            match slot.state.compare_exchange_weak(
+                state,
+                new_state,
+                Ordering::Acquire,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => return Some(Ref { slab: self, key }),
+                Err(actual) => state = actual,
+            }
+        }
+    }
+
+    /// Return a mutable reference to the value associated with the given key.
+    ///
+    /// If the given key is not associated with a value, then `None` is
+    /// returned.
+    pub fn get_mut(&mut self, key: usize) -> Option<&mut T> {
+        let slot = self.slots.get_mut(key)?;
+        if *slot.state.get_mut() != OCCUPIED {
+            return None;
+        }
+        // No `Ref`s can exist while the slab is borrowed mutably
+        Some(unsafe { (*slot.value.get()).assume_init_mut() })
+    }
+
+    /// Return `true` if a value is associated with the given key.
+    pub fn contains(&self, key: usize) -> bool {
+        match self.slots.get(key) {
+            Some(slot) => slot.state.load(Ordering::Acquire) & (OCCUPIED | REMOVED) == OCCUPIED,
+            None => false,
+        }
+    }
+
+    /// Remove the value associated with the given key.
+    ///
+    /// Returns `false` if the key is not associated with a value. If [`Ref`]s
+    /// to the value still exist, it is dropped and the key is released once
+    /// the last of them is dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use slab::sync::Slab;
+    ///
+    /// let slab = Slab::with_capacity(4);
+    /// let key = slab.insert("hello").unwrap();
+    ///
+    /// let value = slab.get(key).unwrap();
+    /// assert!(slab.remove(key));
+    /// assert!(!slab.contains(key));
+    /// assert_eq!(*value, "hello");
+    /// ```
+    pub fn remove(&self, key: usize) -> bool {
+        let slot = match self.slots.get(key) {
+            Some(slot) => slot,
+            None => return false,
+        };
+
+        let mut state = slot.state.load(Ordering::Acquire);
+        loop {
+            if state & (OCCUPIED | REMOVED) != OCCUPIED {
+                return false;
+            }
+            match slot.state.compare_exchange_weak(
+                state,
+                state | REMOVED,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => break,
+                Err(actual) => state = actual,
+            }
+        }
+
+        self.len.fetch_sub(1, Ordering::Relaxed);
+        if state == OCCUPIED {
+            // There are no outstanding references
+            self.release(key);
+        }
+        true
+    }
+
+    /// Remove and return the value associated with the given key.
+    ///
+    /// If the given key is not associated with a value, then `None` is
+    /// returned.
+    pub fn take(&mut self, key: usize) -> Option<T> {
+        let slot = self.slots.get_mut(key)?;
+        if *slot.state.get_mut() != OCCUPIED {
+            return None;
+        }
+        *slot.state.get_mut() = VACANT;
+        let val = unsafe { (*slot.value.get()).assume_init_read() };
+        *self.len.get_mut() -= 1;
+        self.push_vacant(key);
+        Some(val)
+    }
+
+    // Drop the value of a removed slot that is no longer referenced and make
+    // the slot available again.
+    fn release(&self, key: usize) {
+        let slot = &self.slots[key];
+        // Nobody else can access the value anymore
+        let val = unsafe { (*slot.value.get()).assume_init_read() };
+        slot.state.store(VACANT, Ordering::Release);
+        self.push_vacant(key);
+        drop(val);
+    }
+
+    // Pop a vacant slot and claim it for the calling thread.
+    fn pop_vacant(&self) -> Option<usize> {
+        let mut head = self.head.load(Ordering::Acquire);
+        loop {
+            let key = head & self.index_mask;
+            // The index equals the capacity if there are no vacant slots
+            let slot = self.slots.get(key)?;
+            // If another thread pops this slot first, `next` may be stale,
+            // but then the tag of the head has changed and the exchange fails,
+            // unless the tag has wrapped around.
+            let next = slot.next.load(Ordering::Relaxed);
+            match self.head.compare_exchange_weak(
+                head,
+                tagged(self.index_mask, head, next),
+                Ordering::Acquire,
+                Ordering::Acquire,
+            ) {
+                Ok(_) if self.claim(key) => return Some(key),
+                // The head was stale and the slot is in use by another
+                // thread, so it is dropped from the vacant stack
+                Ok(_) => head = self.head.load(Ordering::Acquire),
+                Err(actual) => head = actual,
+            }
+        }
+    }
+
+    // Take exclusive access to a popped slot, which fails if the slot was
+    // not vacant because it was popped from a stale head.
+    fn claim(&self, key: usize) -> bool {
+        self.slots[key]
+            .state
+            .compare_exchange(VACANT, CLAIMED, Ordering::Acquire, Ordering::Relaxed)
+            .is_ok()
+    }
+
+    fn push_vacant(&self, key: usize) {
+        let slot = &self.slots[key];
+        let mut head = self.head.load(Ordering::Relaxed);
+        loop {
+            slot.next.store(head & self.index_mask, Ordering::Relaxed);
+            match self.head.compare_exchange_weak(
+                head,
+                tagged(self.index_mask, head, key),
+                Ordering::Release,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => return,
+                Err(actual) => head = actual,
+            }
+        }
+    }
+}
+
+impl<T> Drop for Slab<T> {
+    fn drop(&mut self) {
+        for slot in self.slots.iter_mut() {
+            if *slot.state.get_mut() & OCCUPIED != 0 {
+                unsafe { ptr::drop_in_place((*slot.value.get()).as_mut_ptr()) }
+            }
+        }
+    }
+}
+
+impl<T> fmt::Debug for Slab<T> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("Slab")
+            .field("len", &self.len())
+            .field("cap", &self.capacity())
+            .finish()
+    }
+}
+
+// ===== Ref =====
+
+impl<T> Ref<'_, T> {
+    /// Return the key of the referenced value.
+    pub fn key(&self) -> usize {
+        self.key
+    }
+}
+
+impl<T> ops::Deref for Ref<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // The reference count keeps the value alive
+        unsafe { (*self.slab.slots[self.key].value.get()).assume_init_ref() }
+    }
+}
+
+impl<T> Drop for Ref<'_, T> {
+    fn drop(&mut self) {
+        let slot = &self.slab.slots[self.key];
+        let prev = slot.state.fetch_sub(REF, Ordering::AcqRel);
+        if prev == OCCUPIED | REMOVED | REF {
+            // This was the last reference to a removed value
+            self.slab.release(self.key);
+        }
+    }
+}
+
+impl<T> fmt::Debug for Ref<'_, T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, fmt)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stale_head_after_tag_wrap() {
+        let mut slab = Slab::with_capacity(3);
+        // Leave two bits for the tag, so that it wraps after four updates
+        slab.index_mask = usize::MAX >> 2;
+
+        // A thread starts popping slot 0 and is suspended before the exchange
+        let head = slab.head.load(Ordering::Acquire);
+        let next = slab.slots[0].next.load(Ordering::Relaxed);
+        assert_eq!((head & slab.index_mask, next), (0, 1));
+
+        // Other threads fill the slab and release slot 0 again, which
+        // brings back the same head after four updates
+        let keys: Vec<_> = (0..3).map(|i| slab.insert(i).unwrap()).collect();
+        assert_eq!(keys, [0, 1, 2]);
+        assert!(slab.remove(0));
+        assert_eq!(slab.head.load(Ordering::Acquire), head);
+
+        // The suspended thread installs its stale `next` and claims slot 0
+        slab.head
+            .compare_exchange(
+                head,
+                tagged(slab.index_mask, head, next),
+                Ordering::Acquire,
+                Ordering::Acquire,
+            )
+            .unwrap();
+        assert!(slab.claim(0));
+        unsafe { (*slab.slots[0].value.get()).write(3) };
+        slab.slots[0].state.store(OCCUPIED, Ordering::Release);
+
+        // Slots 1 and 2 are now on the vacant stack, but in use
+        assert_eq!(slab.insert(4), Err(4));
+        assert_eq!(*slab.get(1).unwrap(), 1);
+        assert_eq!(*slab.get(2).unwrap(), 2);
+
+        // They are reused once they are actually released
+        assert!(slab.remove(2));
+        assert_eq!(slab.insert(5), Ok(2));
+    }
+}
@@ -0,0 +1,248 @@
+//! A hashed timing wheel whose timers are stored in a slab.
+//!
+//! Time is measured in ticks, an unsigned counter whose unit is up to the
+//! caller. The wheel has a fixed number of slots, and a timer is linked into
+//! the slot its deadline maps to, so inserting and cancelling a timer takes
+//! O(1) time. Timers that are more than one revolution of the wheel away share
+//! their slot with nearer ones and are skipped until their deadline is
+//! reached.
+//!
+//! Every timer is identified by its slab key, which stays the same until the
+//! timer fires or is cancelled.
+//!
+//! Nothing in this module reads a clock, so it can be driven by a mock clock
+//! in tests.
+//!
+//! # Examples
+//!
+//! ```
+//! use slab::timer::Wheel;
+//!
+//! let mut wheel = Wheel::new();
+//! wheel.insert(10, "ten");
+//! let key = wheel.insert(20, "twenty");
+//! wheel.insert(1000, "thousand");
+//!
+//! assert_eq!(wheel.poll(5).next(), None);
+//! assert_eq!(wheel.poll(15).collect::<Vec<_>>(), ["ten"]);
+//!
+//! assert_eq!(wheel.cancel(key), Some("twenty"));
+//! assert_eq!(wheel.poll(999).next(), None);
+//! assert_eq!(wheel.poll(1000).collect::<Vec<_>>(), ["thousand"]);
+//! assert!(wheel.is_empty());
+//! ```
+
+use alloc::boxed::Box;
+use alloc::vec;
+use core::cmp;
+use core::fmt;
+use core::iter::FusedIterator;
+
+use crate::Slab;
+
+const DEFAULT_SLOTS: usize = 256;
+
+/// A hashed timing wheel.
+///
+/// See the [module documentation] for more details.
+///
+/// [module documentation]: index.html
+pub struct Wheel<T> {
+    timers: Slab<Timer<T>>,
+
+    // Key of the first timer linked into each slot
+    slots: Box<[Option<usize>]>,
+
+    // The earliest tick whose slot may still hold expired timers. It only
+    // moves forward, and timers with earlier deadlines are linked into its
+    // slot.
+    elapsed: u64,
+}
+
+struct Timer<T> {
+    deadline: u64,
+    value: T,
+    slot: usize,
+    prev: Option<usize>,
+    next: Option<usize>,
+}
+
+/// An iterator over the values of expired timers, returned by
+/// [`Wheel::poll`].
+///
+/// Timers are removed from the wheel as they are returned. Expired timers
+/// that have not been returned when the iterator is dropped are returned by
+/// the next poll instead.
+pub struct Poll<'a, T> {
+    wheel: &'a mut Wheel<T>,
+    now: u64,
+    tick: u64,
+    last: u64,
+    cursor: Option<usize>,
+}
+
+impl<T> Wheel<T> {
+    /// Construct a new, empty wheel with 256 slots, starting at tick 0.
+    pub fn new() -> Self {
+        Self::with_slots(DEFAULT_SLOTS)
+    }
+
+    /// Construct a new, empty wheel with at least the given number of slots,
+    /// starting at tick 0.
+    ///
+    /// The number of slots is rounded up to a power of two. More slots mean
+    /// fewer timers to skip per tick, but longer polls after long pauses.
+    pub fn with_slots(slots: usize) -> Self {
+        let slots = cmp::max(slots, 1)
+            .checked_next_power_of_two()
+            .expect("capacity overflow");
+        Self {
+            timers: Slab::new(),
+            slots: vec![None; slots].into_boxed_slice(),
+            elapsed: 0,
+        }
+    }
+
+    /// Return the number of pending timers.
+    pub fn len(&self) -> usize {
+        self.timers.len()
+    }
+
+    /// Return `true` if there are no pending timers.
+    pub fn is_empty(&self) -> bool {
+        self.timers.is_empty()
+    }
+
+    /// Return `true` if a pending timer is associated with the given key.
+    pub fn contains(&self, key: usize) -> bool {
+        self.timers.contains(key)
+    }
+
+    /// Return the deadline of the timer associated with the given key.
+    pub fn deadline(&self, key: usize) -> Option<u64> {
+        self.timers.get(key).map(|timer| timer.deadline)
+    }
+
+    /// Return a reference to the value of the timer associated with the given
+    /// key.
+    pub fn get(&self, key: usize) -> Option<&T> {
+        self.timers.get(key).map(|timer| &timer.value)
+    }
+
+    /// Insert a timer that expires at `deadline`, returning its key.
+    ///
+    /// A deadline that has already passed expires on the next poll.
+    pub fn insert(&mut self, deadline: u64, value: T) -> usize {
+        let slot = self.slot(cmp::max(deadline, self.elapsed));
+        let next = self.slots[slot];
+        let key = self.timers.insert(Timer {
+            deadline,
+            value,
+            slot,
+            prev: None,
+            next,
+        });
+        if let Some(next) = next {
+            self.timers[next].prev = Some(key);
+        }
+        self.slots[slot] = Some(key);
+        key
+    }
+
+    /// Cancel the timer associated with the given key, returning its value.
+    ///
+    /// If the given key is not associated with a pending timer, then `None`
+    /// is returned.
+    pub fn cancel(&mut self, key: usize) -> Option<T> {
+        let timer = self.timers.try_remove(key)?;
+        match timer.prev {
+            Some(prev) => self.timers[prev].next = timer.next,
+            None => self.slots[timer.slot] = timer.next,
+        }
+        if let Some(next) = timer.next {
+            self.timers[next].prev = timer.prev;
+        }
+        Some(timer.value)
+    }
+
+    /// Advance the wheel to `now` and return an iterator over the values of
+    /// all timers whose deadline is no later than `now`.
+    ///
+    /// Each slot is visited at most once per poll, so polling after a long
+    /// pause takes time proportional to the number of slots, not ticks.
+    pub fn poll(&mut self, now: u64) -> Poll<'_, T> {
+        let tick = self.elapsed;
+        let last = if now - cmp::min(now, tick) >= self.slots.len() as u64 {
+            tick.saturating_add(self.slots.len() as u64 - 1)
+        } else {
+            cmp::max(now, tick)
+        };
+        let cursor = self.slots[self.slot(tick)];
+        Poll {
+            wheel: self,
+            now,
+            tick,
+            last,
+            cursor,
+        }
+    }
+
+    fn slot(&self, tick: u64) -> usize {
+        (tick & (self.slots.len() as u64 - 1)) as usize
+    }
+}
+
+impl<T> Default for Wheel<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> fmt::Debug for Wheel<T> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("Wheel")
+            .field("len", &self.len())
+            .field("slots", &self.slots.len())
+            .field("elapsed", &self.elapsed)
+            .finish()
+    }
+}
+
+// ===== Poll =====
+
+impl<T> Iterator for Poll<'_, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        loop {
+            while let Some(key) = self.cursor {
+                let timer = &self.wheel.timers[key];
+                self.cursor = timer.next;
+                if timer.deadline <= self.now {
+                    return self.wheel.cancel(key);
+                }
+            }
+
+            if self.tick == self.last {
+                // The slot of `now` is visited again by the next poll, since
+                // timers may still be inserted into it.
+                self.wheel.elapsed = cmp::max(self.now, self.wheel.elapsed);
+                return None;
+            }
+            self.tick += 1;
+            self.wheel.elapsed = self.tick;
+            self.cursor = self.wheel.slots[self.wheel.slot(self.tick)];
+        }
+    }
+}
+
+impl<T> FusedIterator for Poll<'_, T> {}
+
+impl<T> fmt::Debug for Poll<'_, T> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("Poll")
+            .field("now", &self.now)
+            .field("tick", &self.tick)
+            .finish()
+    }
+}
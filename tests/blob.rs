@@ -0,0 +1,80 @@
+#![warn(rust_2018_idioms)]
+
+use slab::blob::BlobSlab;
+
+#[test]
+fn insert_get_remove() {
+    let mut blobs = BlobSlab::new();
+    let empty = blobs.insert(b"");
+    let small = blobs.insert(b"small");
+    let medium = blobs.insert(&[7; 100]);
+    let large = blobs.insert(&vec![9; 10_000]);
+    assert_eq!((empty, small, medium, large), (0, 1, 2, 3));
+    assert_eq!(blobs.len(), 4);
+
+    assert_eq!(blobs.get(empty), Some(&[][..]));
+    assert_eq!(&blobs[small], b"small");
+    assert_eq!(&blobs[medium], &[7; 100][..]);
+    assert_eq!(blobs[large].len(), 10_000);
+    assert_eq!(blobs.get(4), None);
+
+    blobs[small].copy_from_slice(b"SMALL");
+    blobs.get_mut(large).unwrap()[0] = 1;
+    assert_eq!(&blobs[small], b"SMALL");
+    assert_eq!(blobs[large][..2], [1, 9]);
+
+    blobs.remove(small);
+    assert!(!blobs.contains(small));
+    assert!(!blobs.try_remove(small));
+    blobs.remove(large);
+
+    // Keys are reused like they are by `Slab`
+    assert_eq!(blobs.insert(b"again"), large);
+    assert_eq!(blobs.insert(b"and again"), small);
+    assert_eq!(
+        blobs
+            .iter()
+            .map(|(key, blob)| (key, blob.len()))
+            .collect::<Vec<_>>(),
+        [(0, 0), (1, 9), (2, 100), (3, 5)]
+    );
+}
+
+#[test]
+fn compact_releases_pages() {
+    let mut blobs = BlobSlab::new();
+    let keys: Vec<_> = (0..2000u32)
+        .map(|i| blobs.insert(&i.to_le_bytes().repeat(1 + i as usize % 16)))
+        .collect();
+    let large = blobs.insert(&[1; 5000]);
+    let before = blobs.allocated_bytes();
+
+    for &key in keys.iter().step_by(3).chain(keys.iter().skip(1).step_by(3)) {
+        blobs.remove(key);
+    }
+    blobs.compact();
+    assert!(blobs.allocated_bytes() < before);
+
+    // The remaining blobs are unchanged and keep their keys
+    for (i, &key) in keys.iter().enumerate().skip(2).step_by(3) {
+        let i = i as u32;
+        assert_eq!(blobs[key], i.to_le_bytes().repeat(1 + i as usize % 16)[..]);
+    }
+    assert_eq!(blobs[large], [1; 5000][..]);
+    assert_eq!(blobs.len(), 2000 / 3 + 1);
+
+    blobs.clear();
+    assert!(blobs.is_empty());
+    assert_eq!(blobs.allocated_bytes(), 0);
+}
+
+#[test]
+fn debug() {
+    let mut blobs = BlobSlab::new();
+    blobs.insert(b"abc");
+    assert_eq!(
+        format!("{:?}", blobs),
+        "BlobSlab { len: 1, allocated_bytes: 16384 }"
+    );
+    assert_eq!(format!("{:?}", blobs.iter()), "Iter { remaining: 1 }");
+}
@@ -0,0 +1,52 @@
+#![warn(rust_2018_idioms)]
+
+use slab::dense::DenseSlab;
+
+#[test]
+fn insert_remove_keeps_values_packed() {
+    let mut slab = DenseSlab::new();
+    let keys: Vec<_> = (0..5).map(|i| slab.insert(i * 10)).collect();
+    assert_eq!(keys, [0, 1, 2, 3, 4]);
+    assert_eq!(slab.as_slice(), [0, 10, 20, 30, 40]);
+
+    // The last value takes the place of the removed one
+    assert_eq!(slab.remove(keys[1]), 10);
+    assert_eq!(slab.as_slice(), [0, 40, 20, 30]);
+    assert_eq!(slab.keys(), [0, 4, 2, 3]);
+    assert_eq!(slab.index_of(keys[4]), Some(1));
+    assert_eq!(slab[keys[4]], 40);
+
+    // Removing the last value moves nothing
+    assert_eq!(slab.remove(keys[3]), 30);
+    assert_eq!(slab.as_slice(), [0, 40, 20]);
+    assert_eq!(slab.try_remove(keys[3]), None);
+    assert!(!slab.contains(keys[3]));
+
+    // Keys are reused like they are by `Slab`
+    assert_eq!(slab.insert(50), keys[3]);
+    assert_eq!(slab.insert(60), keys[1]);
+    assert_eq!(slab.len(), 5);
+    for (key, &value) in &slab {
+        assert_eq!(slab.get(key), Some(&value));
+    }
+}
+
+#[test]
+fn mutate_through_slice_and_iter() {
+    let mut slab = DenseSlab::with_capacity(3);
+    let a = slab.insert(1);
+    let b = slab.insert(2);
+
+    for value in slab.as_mut_slice() {
+        *value += 1;
+    }
+    for (key, value) in &mut slab {
+        *value *= key + 1;
+    }
+    *slab.get_mut(a).unwrap() += 100;
+    assert_eq!((slab[a], slab[b]), (102, 6));
+
+    slab.clear();
+    assert!(slab.is_empty());
+    assert_eq!(slab.get(a), None);
+}
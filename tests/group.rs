@@ -0,0 +1,43 @@
+#![warn(rust_2018_idioms)]
+
+use slab::group::SlabGroup;
+
+#[test]
+fn keys_shared_across_columns() {
+    let mut group = SlabGroup::with_capacity(2);
+    let a = group.insert(('a', 1u8, String::from("one")));
+    let b = group.insert(('b', 2u8, String::from("two")));
+    let c = group.insert(('c', 3u8, String::from("three")));
+    assert_eq!((a, b, c), (0, 1, 2));
+    assert!(group.capacity() >= 3);
+
+    assert_eq!(group.remove(b), ('b', 2, String::from("two")));
+    assert_eq!(group.try_remove(b), None);
+    assert!(!group.contains(b));
+    assert_eq!(group.len(), 2);
+
+    // The vacant key is reused in every column
+    let d = group.insert(('d', 4, String::from("four")));
+    assert_eq!(d, b);
+
+    let (chars, numbers, names) = group.columns();
+    for (key, _) in chars {
+        assert!(numbers.contains(key));
+        assert!(names.contains(key));
+    }
+    assert_eq!(numbers.iter().map(|(_, &n)| n).sum::<u8>(), 8);
+    assert_eq!(group.get(d), Some((&'d', &4, &String::from("four"))));
+}
+
+#[test]
+fn get_mut_and_clear() {
+    let mut group: SlabGroup<(u32,)> = SlabGroup::new();
+    let key = group.insert((5,));
+    *group.get_mut(key).unwrap().0 += 1;
+    assert_eq!(group.get(key), Some((&6,)));
+    assert!(group.get_mut(key + 1).is_none());
+
+    group.clear();
+    assert!(group.is_empty());
+    assert_eq!(group.insert((7,)), 0);
+}
@@ -0,0 +1,92 @@
+#![warn(rust_2018_idioms)]
+
+use slab::heap::SlabHeap;
+
+use std::cmp::Reverse;
+
+#[test]
+fn push_pop_in_order() {
+    let mut heap = SlabHeap::new();
+    assert_eq!(heap.peek(), None);
+    assert_eq!(heap.pop(), None);
+
+    let keys: Vec<_> = [3, 1, 4, 1, 5, 9, 2, 6]
+        .iter()
+        .map(|&v| heap.push(v))
+        .collect();
+    assert_eq!(heap.len(), 8);
+    assert_eq!(heap.peek(), Some((keys[5], &9)));
+
+    let mut popped = Vec::new();
+    while let Some((key, value)) = heap.pop() {
+        assert!(!heap.contains(key));
+        popped.push(value);
+    }
+    assert_eq!(popped, [9, 6, 5, 4, 3, 2, 1, 1]);
+}
+
+#[test]
+fn change_priority_and_remove() {
+    let mut heap = SlabHeap::new();
+    let keys: Vec<_> = (0..10).map(|i| heap.push(Reverse(i * 10))).collect();
+
+    // Decrease and increase keys
+    heap.change_priority(keys[9], |v| v.0 = 5);
+    heap.change_priority(keys[0], |v| v.0 = 95);
+    assert_eq!(heap.get(keys[9]), Some(&Reverse(5)));
+
+    assert_eq!(heap.remove(keys[4]), Reverse(40));
+    assert_eq!(heap.try_remove(keys[4]), None);
+
+    let popped: Vec<_> = std::iter::from_fn(|| heap.pop()).collect();
+    let expected = [
+        (keys[9], 5),
+        (keys[1], 10),
+        (keys[2], 20),
+        (keys[3], 30),
+        (keys[5], 50),
+        (keys[6], 60),
+        (keys[7], 70),
+        (keys[8], 80),
+        (keys[0], 95),
+    ];
+    assert_eq!(popped, expected.map(|(key, v)| (key, Reverse(v))));
+}
+
+#[test]
+fn random_operations() {
+    // A small LCG keeps the test deterministic without extra dependencies
+    let mut state = 0x2545_f491_4f6c_dd1du64;
+    let mut next = move |bound: u64| {
+        state = state.wrapping_mul(6364136223846793005).wrapping_add(1);
+        (state >> 33) % bound
+    };
+
+    let mut heap = SlabHeap::new();
+    let mut model: Vec<(usize, u64)> = Vec::new();
+
+    for _ in 0..2_000 {
+        match next(4) {
+            0 | 1 => {
+                let value = next(1_000);
+                model.push((heap.push(value), value));
+            }
+            2 if !model.is_empty() => {
+                let i = next(model.len() as u64) as usize;
+                let value = next(1_000);
+                heap.change_priority(model[i].0, |v| *v = value);
+                model[i].1 = value;
+            }
+            3 if !model.is_empty() => {
+                let i = next(model.len() as u64) as usize;
+                let (key, value) = model.swap_remove(i);
+                assert_eq!(heap.remove(key), value);
+            }
+            _ => {}
+        }
+
+        let max = model.iter().map(|&(_, value)| value).max();
+        assert_eq!(heap.peek().map(|(_, &value)| value), max);
+        assert_eq!(heap.len(), model.len());
+    }
+}
@@ -0,0 +1,137 @@
+#![warn(rust_2018_idioms)]
+
+use std::panic::{catch_unwind, resume_unwind, AssertUnwindSafe};
+
+use slab::inplace::InPlaceSlab;
+use slab::Slab;
+
+#[test]
+fn insert_uninit() {
+    let mut slab = InPlaceSlab::new();
+    let a = slab.insert(String::from("a"));
+    slab.insert(String::from("b"));
+    slab.remove(a);
+
+    let key = slab.insert_uninit(|key, slot| slot.write(key.to_string()));
+    assert_eq!(key, a);
+    assert_eq!(slab[key], "0");
+    assert_eq!(slab.len(), 2);
+
+    // A full slab grows
+    let key = slab.insert_uninit(|key, slot| slot.write(key.to_string()));
+    assert_eq!(key, 2);
+    assert_eq!(slab[key], "2");
+    assert_eq!(slab.insert(String::new()), 3);
+}
+
+#[test]
+fn insert_uninit_panic_leaves_slab_unchanged() {
+    let mut slab = InPlaceSlab::new();
+    let a = slab.insert(vec![1]);
+    slab.insert(vec![2]);
+    slab.remove(a);
+
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        slab.insert_uninit(|_, slot| {
+            // Overwrite the next vacant key before panicking
+            slot.write(vec![3; 100]);
+            panic!("test")
+        })
+    }));
+    match result {
+        Err(ref payload) if payload.downcast_ref() == Some(&"test") => {}
+        Err(bug) => resume_unwind(bug),
+        Ok(_) => unreachable!(),
+    }
+
+    assert_eq!(slab.len(), 1);
+    assert!(!slab.contains(a));
+    assert_eq!(slab.insert(vec![4]), a);
+    assert_eq!(slab.insert(vec![5]), 2);
+}
+
+#[test]
+#[should_panic(expected = "must return the reference to its slot")]
+fn insert_uninit_other_reference_panics() {
+    let mut slab = InPlaceSlab::new();
+    slab.insert_uninit(|_, _| Box::leak(Box::new(1)));
+}
+
+#[test]
+fn reuses_keys_like_slab() {
+    let mut slab = Slab::new();
+    let mut inplace = InPlaceSlab::new();
+    for i in 0..10 {
+        assert_eq!(inplace.insert(i), slab.insert(i));
+    }
+    for &key in &[3, 7, 1] {
+        assert_eq!(inplace.remove(key), slab.remove(key));
+    }
+    assert_eq!(inplace.try_remove(7), None);
+    for i in 0..5 {
+        assert_eq!(inplace.vacant_key(), slab.vacant_key());
+        let key = inplace.insert_uninit(|_, slot| slot.write(i));
+        assert_eq!(key, slab.insert(i));
+    }
+    assert_eq!(inplace.len(), slab.len());
+    assert!(inplace.iter().eq(slab.iter()));
+}
+
+#[test]
+fn iter_mut_and_clear() {
+    let mut slab = InPlaceSlab::with_capacity(4);
+    for i in 0..4 {
+        slab.insert(i);
+    }
+    slab.remove(2);
+    for (key, value) in &mut slab {
+        *value += key * 10;
+    }
+    let values: Vec<_> = slab.iter().rev().map(|(_, &value)| value).collect();
+    assert_eq!(values, [33, 11, 0]);
+
+    slab.clear();
+    assert!(slab.is_empty());
+    assert_eq!(slab.capacity(), 4);
+    assert_eq!(slab.insert(5), 0);
+}
+
+#[test]
+fn iter_mut_from_both_ends() {
+    let mut slab = InPlaceSlab::new();
+    for i in 0..6 {
+        slab.insert(i);
+    }
+    slab.remove(0);
+    slab.remove(4);
+    slab.remove(5);
+
+    let mut iter = slab.iter_mut();
+    assert_eq!(iter.next_back(), Some((3, &mut 3)));
+    assert_eq!(iter.next(), Some((1, &mut 1)));
+    assert_eq!(iter.next_back(), Some((2, &mut 2)));
+    assert_eq!(iter.next(), None);
+    assert_eq!(iter.next_back(), None);
+}
+
+#[test]
+fn clone_and_drop_values() {
+    use std::rc::Rc;
+
+    let value = Rc::new(());
+    let mut slab = InPlaceSlab::new();
+    for _ in 0..4 {
+        slab.insert(Rc::clone(&value));
+    }
+    slab.remove(1);
+
+    let clone = slab.clone();
+    assert_eq!(Rc::strong_count(&value), 7);
+    assert!(clone.iter().map(|(key, _)| key).eq([0, 2, 3]));
+    assert_eq!(clone.vacant_key(), 1);
+
+    slab.clear();
+    assert_eq!(Rc::strong_count(&value), 4);
+    drop(clone);
+    assert_eq!(Rc::strong_count(&value), 1);
+}
@@ -0,0 +1,103 @@
+#![cfg(feature = "std")]
+#![warn(rust_2018_idioms)]
+
+use std::hash::{BuildHasherDefault, Hasher};
+
+use slab::intern::Interner;
+
+#[test]
+fn intern_and_resolve() {
+    let mut interner = Interner::new();
+    let a = interner.intern("a");
+    let b = interner.intern("b");
+    assert_ne!(a, b);
+    assert_eq!(interner.intern("a"), a);
+    assert_eq!(interner.len(), 2);
+
+    assert_eq!(interner.resolve(a), &"a");
+    assert_eq!(interner[b], "b");
+    assert_eq!(interner.try_resolve(7), None);
+    assert_eq!(interner.get("b"), Some(b));
+    assert_eq!(interner.get("c"), None);
+    assert_eq!(interner.ref_count(a), 2);
+    assert_eq!(interner.ref_count(b), 1);
+    assert_eq!(interner.iter().collect::<Vec<_>>(), [(a, &"a"), (b, &"b")]);
+}
+
+#[test]
+fn release_reuses_keys() {
+    let mut interner = Interner::with_capacity(4);
+    let a = interner.intern(String::from("a"));
+    interner.intern(String::from("a"));
+    let b = interner.intern(String::from("b"));
+
+    assert_eq!(interner.release(a), None);
+    assert!(interner.contains(a));
+    assert_eq!(interner.release(a), Some(String::from("a")));
+    assert!(!interner.contains(a));
+    assert_eq!(interner.ref_count(a), 0);
+    assert_eq!(interner.get("a"), None);
+
+    // The key of "a" goes back to the vacant list
+    let c = interner.intern(String::from("c"));
+    assert_eq!(c, a);
+    assert_eq!(interner.get("c"), Some(c));
+    assert_eq!(interner.get("b"), Some(b));
+    assert_eq!(interner.ref_count(c), 1);
+
+    interner.clear();
+    assert!(interner.is_empty());
+    assert_eq!(interner.get("b"), None);
+}
+
+#[test]
+#[should_panic(expected = "invalid key")]
+fn release_invalid_key() {
+    let mut interner = Interner::new();
+    let key = interner.intern(1);
+    interner.release(key);
+    interner.release(key);
+}
+
+// Every value hashes the same, so all of them share a bucket
+#[derive(Default)]
+struct CollidingHasher;
+
+impl Hasher for CollidingHasher {
+    fn finish(&self) -> u64 {
+        0
+    }
+
+    fn write(&mut self, _bytes: &[u8]) {}
+}
+
+#[test]
+fn hash_collisions() {
+    let mut interner = Interner::with_hasher(BuildHasherDefault::<CollidingHasher>::default());
+    let keys: Vec<_> = (0..4).map(|i| interner.intern(i)).collect();
+    for (i, &key) in keys.iter().enumerate() {
+        assert_eq!(interner.intern(i), key);
+        assert_eq!(interner.get(&i), Some(key));
+    }
+
+    interner.release(keys[1]);
+    assert_eq!(interner.release(keys[1]), Some(1));
+    assert_eq!(interner.get(&1), None);
+    assert_eq!(interner.get(&2), Some(keys[2]));
+
+    for &key in &[keys[0], keys[2]] {
+        interner.release(key);
+        interner.release(key);
+    }
+    // Only one value is left in the bucket
+    assert_eq!(interner.get(&3), Some(keys[3]));
+    assert_eq!(interner.len(), 1);
+    assert_eq!(interner.intern(0), keys[2]);
+}
+
+#[test]
+fn debug() {
+    let mut interner = Interner::new();
+    interner.intern('a');
+    assert_eq!(format!("{:?}", interner), "Interner { len: 1, buckets: 1 }");
+}
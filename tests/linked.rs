@@ -0,0 +1,79 @@
+#![warn(rust_2018_idioms)]
+
+use slab::linked::SlabList;
+
+fn values<T: Copy + PartialEq + std::fmt::Debug>(list: &SlabList<T>) -> Vec<T> {
+    let forward: Vec<_> = list.iter().map(|(_, &value)| value).collect();
+    let mut backward: Vec<_> = list.iter().rev().map(|(_, &value)| value).collect();
+    backward.reverse();
+    assert_eq!(forward, backward);
+    forward
+}
+
+#[test]
+fn push_pop() {
+    let mut list = SlabList::new();
+    assert_eq!(list.pop_front(), None);
+    assert_eq!(list.pop_back(), None);
+
+    list.push_back(2);
+    list.push_front(1);
+    list.push_back(3);
+    assert_eq!(values(&list), [1, 2, 3]);
+    assert_eq!((list.front(), list.back()), (Some(&1), Some(&3)));
+
+    assert_eq!(list.pop_front(), Some(1));
+    assert_eq!(list.pop_back(), Some(3));
+    assert_eq!(list.pop_back(), Some(2));
+    assert!(list.is_empty());
+    assert_eq!((list.front_key(), list.back_key()), (None, None));
+}
+
+#[test]
+fn keys_are_stable() {
+    let mut list = SlabList::new();
+    let a = list.push_back('a');
+    let b = list.push_back('b');
+    let c = list.push_back('c');
+
+    list.move_to_front(c);
+    list.move_to_back(a);
+    assert_eq!(values(&list), ['c', 'b', 'a']);
+    assert_eq!((list[a], list[b], list[c]), ('a', 'b', 'c'));
+    assert_eq!(list.next_key(c), Some(b));
+    assert_eq!(list.prev_key(c), None);
+
+    // Moving a node to the end it is already at does nothing
+    list.move_to_front(c);
+    list.move_to_back(a);
+    assert_eq!(values(&list), ['c', 'b', 'a']);
+
+    assert_eq!(list.remove(b), 'b');
+    assert_eq!(list.try_remove(b), None);
+    assert_eq!(values(&list), ['c', 'a']);
+
+    // The removed key is reused
+    assert_eq!(list.push_front('d'), b);
+    assert_eq!(values(&list), ['d', 'c', 'a']);
+}
+
+#[test]
+fn insert_before_after() {
+    let mut list = SlabList::new();
+    let b = list.push_back('b');
+    list.insert_before(b, 'a');
+    let c = list.insert_after(b, 'c');
+    list.insert_after(c, 'd');
+    assert_eq!(values(&list), ['a', 'b', 'c', 'd']);
+    assert_eq!(list.back(), Some(&'d'));
+
+    list.clear();
+    assert_eq!(values(&list), []);
+}
+
+#[test]
+#[should_panic(expected = "invalid key")]
+fn move_invalid_key() {
+    let mut list = SlabList::<i32>::new();
+    list.move_to_front(0);
+}
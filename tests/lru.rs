@@ -0,0 +1,52 @@
+#![cfg(feature = "std")]
+#![warn(rust_2018_idioms)]
+
+use slab::lru::LruSlab;
+
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn evicts_least_recently_used() {
+    let evicted = Arc::new(Mutex::new(Vec::new()));
+    let mut cache = LruSlab::new(3).on_evict({
+        let evicted = evicted.clone();
+        move |key, value| evicted.lock().unwrap().push((key, value))
+    });
+
+    for i in 0..3 {
+        assert_eq!(cache.insert(i, i * 10), None);
+    }
+    assert_eq!(cache.get(&0), Some(&0));
+    assert_eq!(cache.peek(&1), Some(&10));
+    assert_eq!(cache.peek_lru(), Some((&1, &10)));
+
+    cache.insert(3, 30);
+    cache.insert(4, 40);
+    assert_eq!(*evicted.lock().unwrap(), [(1, 10), (2, 20)]);
+    assert_eq!(cache.len(), 3);
+
+    let order: Vec<_> = cache.iter().map(|(&key, _)| key).collect();
+    assert_eq!(order, [4, 3, 0]);
+}
+
+#[test]
+fn replace_and_remove() {
+    let mut cache = LruSlab::new(2);
+    cache.insert("a", 1);
+    cache.insert("b", 2);
+
+    // Replacing a value marks the entry as used and doesn't evict
+    assert_eq!(cache.insert("a", 3), Some(1));
+    assert_eq!(cache.peek_lru(), Some((&"b", &2)));
+
+    *cache.get_mut("b").unwrap() += 1;
+    assert_eq!(cache.pop_lru(), Some(("a", 3)));
+    assert_eq!(cache.remove("b"), Some(3));
+    assert_eq!(cache.remove("b"), None);
+    assert!(cache.is_empty());
+
+    cache.insert("c", 4);
+    cache.clear();
+    assert!(!cache.contains_key("c"));
+    assert_eq!(cache.capacity(), 2);
+}
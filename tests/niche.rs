@@ -0,0 +1,116 @@
+#![warn(rust_2018_idioms)]
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::num::NonZeroU32;
+
+use slab::niche::NicheSlab;
+use slab::Slab;
+
+// Counts the bytes allocated by the current thread, so that tests running
+// in parallel don't disturb each other.
+struct CountingAlloc;
+
+thread_local! {
+    static ALLOCATED: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATED.try_with(|allocated| allocated.set(allocated.get() + layout.size()));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+fn allocated_by<R>(f: impl FnOnce() -> R) -> usize {
+    let before = ALLOCATED.with(Cell::get);
+    let result = f();
+    let allocated = ALLOCATED.with(Cell::get) - before;
+    drop(result);
+    allocated
+}
+
+#[test]
+fn entries_are_smaller() {
+    let slab = allocated_by(|| Slab::<Box<u64>>::with_capacity(1000));
+    let niche = allocated_by(|| NicheSlab::<Box<u64>>::with_capacity(1000));
+    assert_eq!(niche, 1000 * std::mem::size_of::<Box<u64>>());
+    assert!(niche < slab);
+
+    let slab = allocated_by(|| Slab::<NonZeroU32>::with_capacity(1000));
+    let niche = allocated_by(|| NicheSlab::<NonZeroU32>::with_capacity(1000));
+    assert_eq!(niche, 1000 * std::mem::size_of::<NonZeroU32>());
+    assert!(niche < slab);
+}
+
+#[test]
+fn reuses_keys_like_slab() {
+    let mut slab = Slab::new();
+    let mut niche = NicheSlab::new();
+    for i in 0..10 {
+        assert_eq!(niche.insert(i), slab.insert(i));
+    }
+    for &key in &[3, 7, 1] {
+        assert_eq!(niche.remove(key), slab.remove(key));
+    }
+    assert_eq!(niche.try_remove(7), None);
+    for i in 0..5 {
+        assert_eq!(niche.vacant_key(), slab.vacant_key());
+        assert_eq!(niche.insert(i), slab.insert(i));
+    }
+    assert_eq!(niche.len(), slab.len());
+}
+
+#[test]
+fn iter_and_key_of() {
+    let mut slab = NicheSlab::with_capacity(4);
+    let keys: Vec<_> = (1..=5).map(|i| slab.insert(Box::new(i))).collect();
+    slab.remove(keys[1]);
+    slab.remove(keys[3]);
+
+    let values: Vec<_> = slab.iter().map(|(key, value)| (key, **value)).collect();
+    assert_eq!(values, [(0, 1), (2, 3), (4, 5)]);
+    assert_eq!(slab.iter().next_back().map(|(key, _)| key), Some(4));
+    assert_eq!(slab.iter().len(), 3);
+
+    for (_, value) in &mut slab {
+        **value *= 10;
+    }
+    assert_eq!(*slab[keys[2]], 30);
+    for &key in &[keys[0], keys[2], keys[4]] {
+        assert_eq!(slab.key_of(&slab[key]), key);
+    }
+}
+
+#[test]
+fn key_of_zst() {
+    let mut slab = NicheSlab::new();
+    slab.insert(());
+    let second = slab.insert(());
+    slab.insert(());
+    assert_eq!(slab.key_of(&slab[second]), second);
+}
+
+#[test]
+#[should_panic(expected = "outside this slab")]
+fn key_of_foreign_reference_panics() {
+    let mut slab = NicheSlab::new();
+    slab.insert(1);
+    slab.key_of(&1);
+}
+
+#[test]
+fn debug() {
+    let mut slab = NicheSlab::new();
+    slab.insert('a');
+    assert_eq!(format!("{:?}", slab), "NicheSlab { len: 1, cap: 4 }");
+    assert_eq!(format!("{:#?}", slab).replace([' ', '\n'], ""), "{0:'a',}");
+    assert_eq!(format!("{:?}", slab.iter()), "Iter { remaining: 1 }");
+}
@@ -0,0 +1,71 @@
+#![warn(rust_2018_idioms)]
+
+use slab::ordered::OrderedSlab;
+
+fn values<T: Copy>(slab: &OrderedSlab<T>) -> Vec<T> {
+    slab.iter().map(|(_, &value)| value).collect()
+}
+
+#[test]
+fn iterates_in_insertion_order() {
+    let mut slab = OrderedSlab::new();
+    let keys: Vec<_> = (0..4).map(|i| slab.insert(i)).collect();
+    assert_eq!(keys, [0, 1, 2, 3]);
+
+    assert_eq!(slab.remove(keys[0]), 0);
+    assert_eq!(slab.remove(keys[2]), 2);
+    assert_eq!(slab.try_remove(keys[2]), None);
+
+    // Reused keys still go to the back
+    let c = slab.insert(4);
+    let d = slab.insert(5);
+    assert_eq!((c, d), (keys[2], keys[0]));
+    assert_eq!(values(&slab), [1, 3, 4, 5]);
+    assert_eq!(slab.first_key(), Some(keys[1]));
+    assert_eq!(slab.last_key(), Some(d));
+
+    slab[c] += 10;
+    *slab.get_mut(d).unwrap() += 10;
+    assert_eq!(values(&slab), [1, 3, 14, 15]);
+    assert_eq!(
+        slab.iter().rev().map(|(key, _)| key).collect::<Vec<_>>(),
+        [d, c, 3, 1]
+    );
+    assert_eq!(slab.len(), 4);
+    assert!(slab.contains(c));
+}
+
+#[test]
+fn drain_in_insertion_order() {
+    let mut slab = OrderedSlab::with_capacity(4);
+    let a = slab.insert("a");
+    slab.insert("b");
+    slab.remove(a);
+    slab.insert("c");
+
+    let mut drain = slab.drain();
+    assert_eq!(drain.len(), 2);
+    assert_eq!(drain.next(), Some("b"));
+    drop(drain);
+
+    // Dropping the iterator removes the rest
+    assert!(slab.is_empty());
+    assert_eq!(slab.insert("d"), 0);
+    assert_eq!(slab.insert("e"), 1);
+    assert_eq!(slab.drain().rev().collect::<Vec<_>>(), ["e", "d"]);
+}
+
+#[test]
+fn debug() {
+    let mut slab = OrderedSlab::new();
+    let a = slab.insert('a');
+    slab.insert('b');
+    slab.remove(a);
+    slab.insert('c');
+
+    assert_eq!(
+        format!("{:#?}", slab).replace([' ', '\n'], ""),
+        "{1:'b',0:'c',}"
+    );
+    assert_eq!(format!("{:?}", slab.drain()), "Drain { remaining: 2 }");
+}
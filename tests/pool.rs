@@ -0,0 +1,127 @@
+#![warn(rust_2018_idioms)]
+
+use slab::pool::Pool;
+
+#[test]
+fn insert_get_remove() {
+    let mut pool = Pool::new();
+    let a = pool.insert('a');
+    let b = pool.insert('b');
+    assert_eq!((a, b), (0, 1));
+    assert_eq!(pool.get(a), Some(&'a'));
+    assert_eq!(pool.len(), 2);
+
+    pool.remove(a);
+    assert!(!pool.contains(a));
+    assert_eq!(pool.get(a), None);
+    assert!(!pool.try_remove(a));
+    assert_eq!(pool.len(), 1);
+
+    // The recycled value is dropped by `insert`
+    assert_eq!(pool.insert('c'), a);
+    assert_eq!(pool[a], 'c');
+}
+
+#[test]
+fn insert_with_recycles() {
+    let mut pool = Pool::new();
+    let key = pool.insert_with(|recycled| {
+        assert_eq!(recycled, None);
+        Some(vec![1, 2, 3])
+    });
+    let ptr = pool[key].as_ptr();
+    pool.remove(key);
+
+    let reused = pool.insert_with(|recycled| {
+        let buf = recycled.unwrap();
+        assert_eq!(buf, &[1, 2, 3]);
+        buf.clear();
+        buf.push(4);
+        None
+    });
+    assert_eq!(reused, key);
+    assert_eq!(pool[key], [4]);
+    assert_eq!(pool[key].as_ptr(), ptr);
+}
+
+#[test]
+fn take_does_not_recycle() {
+    let mut pool = Pool::new();
+    let key = pool.insert(String::from("hello"));
+    assert_eq!(pool.take(key).as_deref(), Some("hello"));
+    assert_eq!(pool.take(key), None);
+
+    pool.insert_with(|recycled| {
+        assert_eq!(recycled, None);
+        Some(String::new())
+    });
+}
+
+#[test]
+fn clear_keeps_values() {
+    let mut pool = Pool::new();
+    for i in 0..3 {
+        pool.insert(i);
+    }
+    pool.remove(1);
+    assert_eq!(pool.iter().collect::<Vec<_>>(), [(0, &0), (2, &2)]);
+
+    pool.clear();
+    assert!(pool.is_empty());
+    assert_eq!(pool.iter().next(), None);
+
+    pool.insert_with(|recycled| {
+        assert_eq!(recycled, Some(&mut 0));
+        Some(10)
+    });
+    pool.clear_recycled();
+    pool.insert_with(|recycled| {
+        assert_eq!(recycled, None);
+        Some(11)
+    });
+    assert_eq!(pool.iter().rev().collect::<Vec<_>>(), [(1, &11), (0, &10)]);
+}
+
+#[test]
+fn insert_with_replaces_recycled() {
+    let mut pool = Pool::new();
+    let key = pool.insert(vec![1]);
+    pool.remove(key);
+
+    pool.insert_with(|recycled| {
+        assert_eq!(recycled.map(|buf| buf.len()), Some(1));
+        Some(vec![2, 3])
+    });
+    assert_eq!(pool[key], [2, 3]);
+}
+
+#[test]
+#[should_panic(expected = "no value to insert")]
+fn insert_with_nothing_to_recycle() {
+    let mut pool = Pool::<u32>::new();
+    pool.insert_with(|_| None);
+}
+
+#[test]
+fn insert_with_panic_keeps_recycled() {
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    let mut pool = Pool::new();
+    let key = pool.insert(vec![1, 2]);
+    pool.remove(key);
+
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        pool.insert_with(|recycled| {
+            recycled.unwrap().push(3);
+            panic!("boom")
+        })
+    }));
+    assert!(result.is_err());
+    assert!(pool.is_empty());
+
+    pool.insert_with(|recycled| {
+        assert_eq!(recycled, Some(&mut vec![1, 2, 3]));
+        None
+    });
+    assert_eq!(pool.len(), 1);
+}
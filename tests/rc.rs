@@ -0,0 +1,110 @@
+#![cfg(feature = "std")]
+#![warn(rust_2018_idioms)]
+
+use slab::rc::{ArcHandle, RcHandle, SlabArc, SlabRc};
+
+use std::cell::RefCell;
+use std::thread;
+
+#[test]
+fn rc_last_handle_releases_key() {
+    let slab = SlabRc::new();
+
+    let a = slab.insert('a');
+    let b = slab.insert('b');
+    assert_eq!((a.key(), b.key()), (0, 1));
+    assert_eq!((*a, *b), ('a', 'b'));
+    assert_eq!(slab.len(), 2);
+
+    let a2 = a.clone();
+    drop(a);
+    assert!(slab.contains(0));
+    assert!(RcHandle::ptr_eq(&a2, &slab.get(0).unwrap()));
+
+    drop(a2);
+    assert!(!slab.contains(0));
+    assert!(slab.get(0).is_none());
+    assert_eq!(slab.len(), 1);
+
+    // The released key is reused
+    let c = slab.insert('c');
+    assert_eq!(c.key(), 0);
+}
+
+#[test]
+fn rc_handle_outlives_slab() {
+    let slab = SlabRc::new();
+    let handle = slab.insert(String::from("hello"));
+    drop(slab);
+    assert_eq!(*handle, "hello");
+}
+
+#[test]
+fn rc_value_drops_handles() {
+    // A value holding a handle to the same slab must be able to drop it
+    struct Node(RefCell<Option<RcHandle<Node>>>);
+
+    let slab = SlabRc::new();
+    let first = slab.insert(Node(RefCell::new(None)));
+    let second = slab.insert(Node(RefCell::new(None)));
+    *second.0.borrow_mut() = Some(first.clone());
+    drop(first);
+    assert_eq!(slab.len(), 2);
+
+    drop(second);
+    assert!(slab.is_empty());
+}
+
+#[test]
+fn arc_concurrent_handles() {
+    const THREADS: usize = 4;
+    const ITERATIONS: usize = if cfg!(miri) { 20 } else { 1_000 };
+
+    let slab = SlabArc::new();
+
+    thread::scope(|scope| {
+        for thread in 0..THREADS {
+            let slab = &slab;
+            scope.spawn(move || {
+                for i in 0..ITERATIONS {
+                    let handle = slab.insert((thread, i));
+                    let other = slab.get(handle.key()).unwrap();
+                    assert!(ArcHandle::ptr_eq(&handle, &other));
+                    drop(handle);
+                    assert_eq!(*other, (thread, i));
+                }
+            });
+        }
+    });
+
+    assert!(slab.is_empty());
+}
+
+#[test]
+fn rc_values_live_in_slab_pages() {
+    let slab = SlabRc::with_capacity(3);
+    let capacity = slab.capacity();
+    assert!(capacity >= 3);
+
+    let handles: Vec<_> = (0..capacity).map(|i| slab.insert(i)).collect();
+    assert_eq!(slab.capacity(), capacity);
+    let first: *const usize = &*handles[0];
+
+    // Growing the slab doesn't move values that have handles
+    let more: Vec<_> = (0..capacity * 4).map(|i| slab.insert(i)).collect();
+    assert!(slab.capacity() > capacity);
+    assert_eq!(&*handles[0] as *const usize, first);
+    for (i, handle) in handles.iter().chain(&more).enumerate() {
+        assert_eq!(handle.key(), i);
+    }
+
+    // Released entries are reused, the last released one first, without
+    // allocating
+    let capacity = slab.capacity();
+    let last = more.last().unwrap().key();
+    drop(handles);
+    drop(more);
+    assert!(slab.is_empty());
+    assert_eq!(slab.insert(10).key(), last);
+    assert_eq!(slab.capacity(), capacity);
+}
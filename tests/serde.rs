@@ -1,9 +1,14 @@
 #![cfg(feature = "serde")]
 #![warn(rust_2018_idioms)]
 
+use serde::de::value::MapDeserializer;
+use serde::de::DeserializeSeed;
 use serde::{Deserialize, Serialize};
-use serde_test::{assert_tokens, Token};
+use serde_test::{assert_de_tokens, assert_de_tokens_error, assert_tokens, Token};
+use slab::niche::NicheSlab;
+use slab::ordered::OrderedSlab;
 use slab::Slab;
+use slab::SlabSeed;
 
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(transparent)]
@@ -31,7 +36,7 @@ fn test_serde_empty() {
 
 #[test]
 fn test_serde() {
-    let vec = vec![(1, 2), (3, 4), (5, 6)];
+    let vec = [(1, 2), (3, 4), (5, 6)];
     let slab: Slab<_> = vec.iter().cloned().collect();
     assert_tokens(
         &SlabPartialEq(slab),
@@ -47,3 +52,552 @@ fn test_serde() {
         ],
     );
 }
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(transparent)]
+struct ExactSlab(#[serde(with = "slab::repr::exact")] Slab<i32>);
+
+impl PartialEq for ExactSlab {
+    fn eq(&self, other: &Self) -> bool {
+        let (mut this, mut other) = (self.0.clone(), other.0.clone());
+        if this.capacity() != other.capacity()
+            || this.iter().ne(other.iter())
+            || this.vacant_key() != other.vacant_key()
+        {
+            return false;
+        }
+        // Both slabs must hand out the same keys until they are full
+        while this.len() < this.capacity() {
+            if this.insert(0) != other.insert(0) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+#[test]
+fn test_serde_exact_empty() {
+    let slab = Slab::<i32>::new();
+    assert_tokens(
+        &ExactSlab(slab),
+        &[
+            Token::Tuple { len: 3 },
+            Token::U64(0),
+            Token::Seq { len: Some(0) },
+            Token::SeqEnd,
+            Token::Seq { len: Some(0) },
+            Token::SeqEnd,
+            Token::TupleEnd,
+        ],
+    );
+}
+
+#[test]
+fn test_serde_exact() {
+    let mut slab = Slab::with_capacity(6);
+    for i in 0..5 {
+        slab.insert(i);
+    }
+    slab.remove(1);
+    slab.remove(3);
+    slab.remove(0);
+    assert_tokens(
+        &ExactSlab(slab),
+        &[
+            Token::Tuple { len: 3 },
+            Token::U64(6),
+            Token::Seq { len: Some(5) },
+            Token::None,
+            Token::None,
+            Token::Some,
+            Token::I32(2),
+            Token::None,
+            Token::Some,
+            Token::I32(4),
+            Token::SeqEnd,
+            Token::Seq { len: Some(3) },
+            Token::U64(0),
+            Token::U64(3),
+            Token::U64(1),
+            Token::SeqEnd,
+            Token::TupleEnd,
+        ],
+    );
+}
+
+#[test]
+fn test_serde_exact_invalid_vacant_keys() {
+    let entries = [
+        Token::Tuple { len: 3 },
+        Token::U64(0),
+        Token::Seq { len: Some(3) },
+        Token::None,
+        Token::Some,
+        Token::I32(1),
+        Token::None,
+        Token::SeqEnd,
+        Token::Seq { len: Some(2) },
+    ];
+
+    // Occupied entry
+    let tokens = [&entries[..], &[Token::U64(1)]].concat();
+    assert_de_tokens_error::<ExactSlab>(
+        &tokens,
+        "invalid value: integer `1`, expected the key of an unlisted vacant entry",
+    );
+
+    // Listed twice
+    let tokens = [&entries[..], &[Token::U64(0), Token::U64(0)]].concat();
+    assert_de_tokens_error::<ExactSlab>(
+        &tokens,
+        "invalid value: integer `0`, expected the key of an unlisted vacant entry",
+    );
+
+    // Missing vacant entry
+    let tokens = [&entries[..], &[Token::U64(2), Token::SeqEnd]].concat();
+    assert_de_tokens_error::<ExactSlab>(
+        &tokens,
+        "invalid length 1, expected one key for every vacant entry",
+    );
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(transparent)]
+struct DenseSlab(#[serde(with = "slab::repr::dense")] Slab<i32>);
+
+impl PartialEq for DenseSlab {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.iter().eq(other.0.iter())
+    }
+}
+
+#[test]
+fn test_serde_dense() {
+    let mut slab = Slab::new();
+    for i in 0..4 {
+        slab.insert(i);
+    }
+    slab.remove(1);
+    assert_tokens(
+        &DenseSlab(slab),
+        &[
+            Token::Seq { len: Some(4) },
+            Token::Some,
+            Token::I32(0),
+            Token::None,
+            Token::Some,
+            Token::I32(2),
+            Token::Some,
+            Token::I32(3),
+            Token::SeqEnd,
+        ],
+    );
+}
+
+#[test]
+fn test_serde_dense_trailing_vacant() {
+    let tokens = [
+        Token::Seq { len: Some(4) },
+        Token::None,
+        Token::Some,
+        Token::I32(1),
+        Token::None,
+        Token::None,
+        Token::SeqEnd,
+    ];
+    let mut slab = Slab::new();
+    slab.insert(0);
+    slab.insert(1);
+    slab.remove(0);
+    assert_de_tokens(&DenseSlab(slab), &tokens);
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(transparent)]
+struct BoundedSlab(#[serde(deserialize_with = "bounded")] Slab<i32>);
+
+fn bounded<'de, D>(deserializer: D) -> Result<Slab<i32>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    SlabSeed::new()
+        .max_key(9)
+        .max_len(2)
+        .deserialize(deserializer)
+}
+
+impl PartialEq for BoundedSlab {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.iter().eq(other.0.iter())
+    }
+}
+
+#[test]
+fn test_serde_seed_within_limits() {
+    let slab: Slab<_> = [(9, 1), (3, 2)].iter().cloned().collect();
+    assert_de_tokens(
+        &BoundedSlab(slab),
+        &[
+            Token::Map {
+                len: Some(usize::MAX),
+            },
+            Token::U64(9),
+            Token::I32(1),
+            Token::U64(3),
+            Token::I32(2),
+            Token::U64(3),
+            Token::I32(2),
+            Token::MapEnd,
+        ],
+    );
+}
+
+#[test]
+fn test_serde_seed_max_key() {
+    assert_de_tokens_error::<BoundedSlab>(
+        &[
+            Token::Map { len: Some(1) },
+            Token::U64(18446744073709551000),
+            Token::I32(1),
+        ],
+        "invalid value: integer `18446744073709551000`, expected a key no greater than 9",
+    );
+}
+
+#[test]
+fn test_serde_seed_max_len() {
+    assert_de_tokens_error::<BoundedSlab>(
+        &[
+            Token::Map { len: None },
+            Token::U64(0),
+            Token::I32(1),
+            Token::U64(1),
+            Token::I32(2),
+            Token::U64(2),
+            Token::I32(3),
+        ],
+        "invalid length 3, expected at most 2 values",
+    );
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(transparent)]
+struct CappedSlab(#[serde(deserialize_with = "capped")] Slab<i32>);
+
+fn capped<'de, D>(deserializer: D) -> Result<Slab<i32>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    SlabSeed::new().max_capacity(4).deserialize(deserializer)
+}
+
+impl PartialEq for CappedSlab {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.iter().eq(other.0.iter())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(transparent)]
+struct CappedExactSlab(#[serde(deserialize_with = "capped_exact")] Slab<i32>);
+
+fn capped_exact<'de, D>(deserializer: D) -> Result<Slab<i32>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    SlabSeed::new()
+        .max_capacity(4)
+        .exact()
+        .deserialize(deserializer)
+}
+
+impl PartialEq for CappedExactSlab {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.iter().eq(other.0.iter())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(transparent)]
+struct CappedDenseSlab(#[serde(deserialize_with = "capped_dense")] Slab<i32>);
+
+fn capped_dense<'de, D>(deserializer: D) -> Result<Slab<i32>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    SlabSeed::new().max_len(1).dense().deserialize(deserializer)
+}
+
+impl PartialEq for CappedDenseSlab {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.iter().eq(other.0.iter())
+    }
+}
+
+#[test]
+fn test_serde_seed_max_capacity() {
+    let slab: Slab<_> = [(3, 1)].iter().cloned().collect();
+    assert_de_tokens(
+        &CappedSlab(slab),
+        &[
+            Token::Map { len: Some(1) },
+            Token::U64(3),
+            Token::I32(1),
+            Token::MapEnd,
+        ],
+    );
+    assert_de_tokens_error::<CappedSlab>(
+        &[Token::Map { len: Some(1) }, Token::U64(4), Token::I32(1)],
+        "invalid value: integer `4`, expected a key below the maximum capacity of 4",
+    );
+}
+
+#[test]
+fn test_serde_seed_exact_max_capacity() {
+    let mut slab = Slab::with_capacity(4);
+    slab.insert(1);
+    assert_de_tokens(
+        &CappedExactSlab(slab),
+        &[
+            Token::Tuple { len: 3 },
+            Token::U64(4),
+            Token::Seq { len: Some(1) },
+            Token::Some,
+            Token::I32(1),
+            Token::SeqEnd,
+            Token::Seq { len: Some(0) },
+            Token::SeqEnd,
+            Token::TupleEnd,
+        ],
+    );
+    assert_de_tokens_error::<CappedExactSlab>(
+        &[Token::Tuple { len: 3 }, Token::U64(usize::MAX as u64)],
+        "invalid length 18446744073709551615, expected a capacity of at most 4",
+    );
+    assert_de_tokens_error::<CappedExactSlab>(
+        &[
+            Token::Tuple { len: 3 },
+            Token::U64(4),
+            Token::Seq { len: None },
+            Token::None,
+            Token::None,
+            Token::None,
+            Token::None,
+            Token::None,
+        ],
+        "invalid length 5, expected a capacity of at most 4",
+    );
+}
+
+#[test]
+fn test_serde_seed_dense_max_len() {
+    let slab: Slab<_> = [(1, 1)].iter().cloned().collect();
+    assert_de_tokens(
+        &CappedDenseSlab(slab),
+        &[
+            Token::Seq { len: Some(3) },
+            Token::None,
+            Token::Some,
+            Token::I32(1),
+            Token::None,
+            Token::SeqEnd,
+        ],
+    );
+    assert_de_tokens_error::<CappedDenseSlab>(
+        &[
+            Token::Seq { len: Some(3) },
+            Token::Some,
+            Token::I32(1),
+            Token::None,
+            Token::Some,
+            Token::I32(2),
+        ],
+        "invalid length 2, expected at most 1 values",
+    );
+}
+
+fn deserialize_in_place(slab: &mut Slab<String>, pairs: &[(usize, &str)]) {
+    let deserializer = MapDeserializer::<_, serde::de::value::Error>::new(pairs.iter().cloned());
+    Slab::deserialize_in_place(deserializer, slab).unwrap();
+}
+
+#[test]
+fn test_serde_deserialize_in_place() {
+    let mut slab = Slab::with_capacity(8);
+    for i in 0..6 {
+        slab.insert(i.to_string());
+    }
+    slab.remove(2);
+    let buffer = slab[3].as_ptr();
+
+    deserialize_in_place(&mut slab, &[(0, "a"), (2, "b"), (3, "c"), (7, "d")]);
+
+    assert_eq!(slab.len(), 4);
+    assert_eq!(slab.capacity(), 8);
+    assert_eq!(
+        slab.iter().collect::<Vec<_>>(),
+        [
+            (0, &"a".to_owned()),
+            (2, &"b".to_owned()),
+            (3, &"c".to_owned()),
+            (7, &"d".to_owned())
+        ]
+    );
+    // The existing string was reused
+    assert_eq!(slab[3].as_ptr(), buffer);
+    // Vacant entries are reused lowest first
+    assert_eq!(slab.insert(String::new()), 1);
+    assert_eq!(slab.insert(String::new()), 4);
+}
+
+#[test]
+fn test_serde_deserialize_in_place_unsorted() {
+    let mut slab: Slab<String> = Slab::new();
+    for i in 0..8 {
+        slab.insert(i.to_string());
+    }
+
+    deserialize_in_place(&mut slab, &[(3, "a"), (1, "b"), (3, "c"), (2, "d")]);
+
+    assert_eq!(
+        slab.iter().collect::<Vec<_>>(),
+        [
+            (1, &"b".to_owned()),
+            (2, &"d".to_owned()),
+            (3, &"c".to_owned())
+        ]
+    );
+    assert_eq!(slab.vacant_key(), 0);
+    assert_eq!(slab.insert(String::new()), 0);
+    assert_eq!(slab.insert(String::new()), 4);
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(transparent)]
+struct OrderedPartialEq(OrderedSlab<i32>);
+
+impl PartialEq for OrderedPartialEq {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.iter().eq(other.0.iter())
+    }
+}
+
+#[test]
+fn test_serde_ordered() {
+    let mut slab = OrderedSlab::new();
+    let a = slab.insert(1);
+    slab.insert(2);
+    slab.remove(a);
+    slab.insert(3);
+    slab.insert(4);
+    assert_tokens(
+        &OrderedPartialEq(slab),
+        &[
+            Token::Map { len: Some(3) },
+            Token::U64(1),
+            Token::I32(2),
+            Token::U64(0),
+            Token::I32(3),
+            Token::U64(2),
+            Token::I32(4),
+            Token::MapEnd,
+        ],
+    );
+}
+
+#[test]
+fn test_serde_ordered_restores_order() {
+    let pairs = [(3, 30), (1, 10)];
+    let deserializer = MapDeserializer::<_, serde::de::value::Error>::new(pairs.iter().cloned());
+    let mut slab = OrderedSlab::<i32>::deserialize(deserializer).unwrap();
+
+    assert_eq!(slab.first_key(), Some(3));
+    assert_eq!(slab.last_key(), Some(1));
+    // The vacant keys are reused lowest first
+    assert_eq!(slab.insert(0), 0);
+    assert_eq!(slab.insert(20), 2);
+    assert_eq!(slab.drain().collect::<Vec<_>>(), [30, 10, 0, 20]);
+}
+
+#[test]
+fn test_serde_ordered_duplicate_key() {
+    assert_de_tokens_error::<OrderedPartialEq>(
+        &[
+            Token::Map { len: Some(2) },
+            Token::U64(1),
+            Token::I32(10),
+            Token::U64(1),
+            Token::I32(20),
+            Token::MapEnd,
+        ],
+        "duplicate key 1",
+    );
+}
+
+#[test]
+fn test_serde_niche() {
+    let mut slab = NicheSlab::new();
+    for i in 0..4 {
+        slab.insert(Box::new(i));
+    }
+    slab.remove(0);
+    slab.remove(2);
+
+    let pairs = [(1, 1), (3, 3)];
+    let deserializer = MapDeserializer::<_, serde::de::value::Error>::new(pairs.iter().cloned());
+    let mut deserialized = NicheSlab::<Box<i32>>::deserialize(deserializer).unwrap();
+    assert!(deserialized.iter().eq(slab.iter()));
+
+    assert_tokens(
+        &NichePartialEq(slab),
+        &[
+            Token::Map { len: Some(2) },
+            Token::U64(1),
+            Token::I32(1),
+            Token::U64(3),
+            Token::I32(3),
+            Token::MapEnd,
+        ],
+    );
+
+    // The vacant keys are reused lowest first
+    assert_eq!(deserialized.insert(Box::new(0)), 0);
+    assert_eq!(deserialized.insert(Box::new(2)), 2);
+    assert_eq!(deserialized.insert(Box::new(4)), 4);
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(transparent)]
+struct NichePartialEq(NicheSlab<Box<i32>>);
+
+impl PartialEq for NichePartialEq {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.iter().eq(other.0.iter())
+    }
+}
+
+#[test]
+fn test_serde_glob_import() {
+    // `use slab::*` must not bring a module that conflicts with the `serde`
+    // crate into scope
+    mod glob {
+        use serde::Serialize;
+        use slab::*;
+
+        #[derive(Serialize)]
+        pub(super) struct Wrapper(pub(super) Slab<i32>);
+    }
+
+    let slab: Slab<_> = [(1, 2)].iter().cloned().collect();
+    serde_test::assert_ser_tokens(
+        &glob::Wrapper(slab),
+        &[
+            Token::NewtypeStruct { name: "Wrapper" },
+            Token::Map { len: Some(1) },
+            Token::U64(1),
+            Token::I32(2),
+            Token::MapEnd,
+        ],
+    );
+}
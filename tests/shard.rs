@@ -0,0 +1,137 @@
+#![cfg(feature = "std")]
+#![warn(rust_2018_idioms)]
+
+use slab::shard::ShardedSlab;
+
+use std::collections::HashSet;
+use std::sync::Mutex;
+use std::thread;
+
+#[test]
+fn insert_get_remove_one() {
+    let slab = ShardedSlab::with_shards(1);
+    assert!(slab.is_empty());
+
+    let key = slab.insert(10);
+    assert_eq!(slab.with(key, |&value| value), Some(10));
+    assert_eq!(slab.len(), 1);
+
+    slab.remove(key);
+    assert!(!slab.contains(key));
+    assert!(slab.is_empty());
+    assert_eq!(slab.take(key), None);
+}
+
+#[test]
+fn access_keys_in_same_shard() {
+    let slab = ShardedSlab::with_shards(1);
+    let a = slab.insert(String::from("a"));
+    let b = slab.insert(String::from("b"));
+
+    let a_len = slab.with(a, String::len);
+    let b_len = slab.with(b, String::len);
+    assert_eq!((a_len, b_len), (Some(1), Some(1)));
+    assert!(slab.contains(a) && slab.contains(b));
+}
+
+#[test]
+fn shard_in_high_bits() {
+    let slab = ShardedSlab::with_shards(4);
+    let keys = Mutex::new(Vec::new());
+
+    thread::scope(|scope| {
+        for _ in 0..4 {
+            scope.spawn(|| {
+                let key = slab.insert(());
+                keys.lock().unwrap().push(key);
+            });
+        }
+    });
+
+    let keys = keys.into_inner().unwrap();
+    for &key in &keys {
+        assert!(slab.contains(key));
+        // The low bits only hold the key within the shard
+        assert!(key & (usize::MAX >> 2) < 4);
+    }
+    assert_eq!(keys.iter().collect::<HashSet<_>>().len(), 4);
+    assert_eq!(slab.len(), 4);
+}
+
+#[test]
+fn remote_remove_is_observed() {
+    let slab = ShardedSlab::with_shards(2);
+    let local = slab.insert("local");
+    let shard_of = |key: usize| key >> (usize::BITS - 1);
+
+    // Threads are assigned shards in the order they first use one, which
+    // other tests running concurrently also affect, so keep spawning until
+    // a thread owns the other shard.
+    let remote = loop {
+        let remote = thread::scope(|scope| {
+            scope
+                .spawn(|| {
+                    let remote = slab.insert("remote");
+                    if shard_of(remote) == shard_of(local) {
+                        slab.remove(remote);
+                        return None;
+                    }
+                    slab.remove(local);
+                    Some(remote)
+                })
+                .join()
+                .unwrap()
+        });
+        if let Some(remote) = remote {
+            break remote;
+        }
+    };
+
+    // The other thread's removal is processed as soon as the shard is locked
+    assert!(!slab.contains(local));
+    assert_eq!(slab.with(local, |&value| value), None);
+    assert_eq!(slab.len(), 1);
+
+    let key = slab.insert("new");
+    assert_eq!(key, local);
+    assert_eq!(slab.with(key, |&value| value), Some("new"));
+
+    assert_eq!(slab.take(remote), Some("remote"));
+    slab.remove(key);
+    slab.flush();
+    assert!(slab.is_empty());
+}
+
+#[test]
+fn concurrent_insert_remove() {
+    const THREADS: usize = 8;
+    const ITERATIONS: usize = if cfg!(miri) { 20 } else { 1_000 };
+
+    let slab = ShardedSlab::with_shards(THREADS);
+    let keys = Mutex::new(HashSet::new());
+
+    thread::scope(|scope| {
+        for thread in 0..THREADS {
+            let (slab, keys) = (&slab, &keys);
+            scope.spawn(move || {
+                for i in 0..ITERATIONS {
+                    let key = slab.insert((thread, i));
+                    assert_eq!(slab.with(key, |&value| value), Some((thread, i)));
+                    if i % 2 == 0 {
+                        slab.remove(key);
+                    } else {
+                        assert!(keys.lock().unwrap().insert(key));
+                    }
+                }
+            });
+        }
+    });
+
+    let keys = keys.into_inner().unwrap();
+    slab.flush();
+    assert_eq!(slab.len(), keys.len());
+    for key in keys {
+        assert!(slab.take(key).is_some());
+    }
+    assert!(slab.is_empty());
+}
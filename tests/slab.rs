@@ -2,6 +2,7 @@
 
 use slab::*;
 
+use std::iter;
 use std::panic::{catch_unwind, resume_unwind, AssertUnwindSafe};
 
 #[test]
@@ -441,6 +442,60 @@ fn from_iterator_issue_100() {
     assert_eq!(slab.insert(()), 6);
 }
 
+#[test]
+fn builder_sorted_keys() {
+    let mut builder = SlabBuilder::new();
+    for &key in &[1, 2, 5, 6] {
+        builder.insert_at(key, key).unwrap();
+    }
+    assert_eq!(builder.len(), 4);
+    assert!(!builder.vacant_list_broken());
+
+    let (mut slab, recreated) = builder.build_with_report();
+    assert!(!recreated);
+    assert_eq!(slab.len(), 4);
+    assert_eq!(slab.insert(0), 4);
+    assert_eq!(slab.insert(0), 3);
+    assert_eq!(slab.insert(0), 0);
+    assert_eq!(slab.insert(0), 7);
+}
+
+#[test]
+fn builder_unsorted_keys() {
+    let mut builder = SlabBuilder::with_capacity(8);
+    for &key in &[6, 2, 5] {
+        builder.insert_at(key, key).unwrap();
+    }
+    assert!(builder.vacant_list_broken());
+
+    let (mut slab, recreated) = builder.build_with_report();
+    assert!(recreated);
+    for &key in &[0, 1, 3, 4, 7] {
+        assert_eq!(slab.insert(0), key);
+    }
+}
+
+#[test]
+fn builder_duplicate_keys() {
+    let mut builder = SlabBuilder::new();
+    builder.insert_at(3, 'a').unwrap();
+    builder.insert_at(3, 'b').unwrap();
+    assert_eq!(builder.build()[3], 'b');
+
+    let mut builder = SlabBuilder::new().duplicate_keys(DuplicateKeys::KeepFirst);
+    builder.insert_at(3, 'a').unwrap();
+    builder.insert_at(3, 'b').unwrap();
+    assert_eq!(builder.build()[3], 'a');
+
+    let mut builder = SlabBuilder::new().duplicate_keys(DuplicateKeys::Error);
+    builder.insert_at(3, 'a').unwrap();
+    let err = builder.insert_at(3, 'b').unwrap_err();
+    assert_eq!(err.to_string(), "duplicate key 3");
+    assert_eq!(err.into_value(), 'b');
+    assert_eq!(builder.len(), 1);
+    assert_eq!(builder.build()[3], 'a');
+}
+
 #[test]
 fn clear() {
     let mut slab = Slab::new();
@@ -710,3 +765,235 @@ fn try_remove() {
 fn const_new() {
     static _SLAB: Slab<()> = Slab::new();
 }
+
+#[test]
+fn try_lookups_report_errors() {
+    let mut slab = Slab::new();
+    let a = slab.insert(1);
+    let b = slab.insert(2);
+    slab.remove(a);
+
+    assert_eq!(slab.try_get(b), Ok(&2));
+    assert_eq!(slab.try_get(a), Err(SlabError::Vacant(a)));
+    assert_eq!(slab.try_get(2), Err(SlabError::OutOfBounds(2)));
+    assert_eq!(slab.try_get_mut(a), Err(SlabError::Vacant(a)));
+    *slab.try_get_mut(b).unwrap() += 10;
+    assert_eq!(slab[b], 12);
+
+    assert_eq!(slab.try_get2_mut(a, b), Err(SlabError::Vacant(a)));
+    assert_eq!(slab.try_get2_mut(b, b), Err(SlabError::SameKey(b)));
+    assert_eq!(slab.try_get2_mut(b, 7), Err(SlabError::OutOfBounds(7)));
+    let c = slab.insert(3);
+    assert_eq!(slab.try_get2_mut(c, b), Ok((&mut 3, &mut 12)));
+
+    assert_eq!(slab.try_key_of(&slab[b]), Ok(b));
+    assert_eq!(slab.try_key_of(&12), Err(SlabError::ForeignReference));
+
+    assert_eq!(slab.remove_checked(b), Ok(12));
+    assert_eq!(slab.remove_checked(b), Err(SlabError::Vacant(b)));
+    assert_eq!(slab.remove_checked(9), Err(SlabError::OutOfBounds(9)));
+    assert_eq!(slab.len(), 1);
+}
+
+#[test]
+fn try_reserve() {
+    let mut slab = Slab::<u32>::new();
+    assert_eq!(slab.try_reserve(8), Ok(()));
+    assert!(slab.capacity() >= 8);
+    assert_eq!(
+        slab.try_reserve(usize::MAX),
+        Err(SlabError::CapacityOverflow)
+    );
+    assert_eq!(SlabError::CapacityOverflow.to_string(), "capacity overflow");
+}
+
+#[test]
+fn remove_many() {
+    let mut slab = Slab::new();
+    let keys: Vec<_> = (0..8).map(|i| slab.insert(i.to_string())).collect();
+    slab.remove(keys[6]);
+
+    // Invalid keys leave the slab untouched
+    assert_eq!(
+        slab.remove_many(&[keys[2], keys[6]]),
+        Err(SlabError::Vacant(keys[6]))
+    );
+    assert_eq!(
+        slab.remove_many(&[keys[2], keys[4], keys[2]]),
+        Err(SlabError::SameKey(keys[2]))
+    );
+    assert_eq!(slab.remove_many(&[20]), Err(SlabError::OutOfBounds(20)));
+    assert_eq!(slab.len(), 7);
+    assert_eq!(slab.vacant_key(), keys[6]);
+
+    let removed = slab.remove_many(&[keys[5], keys[0], keys[3]]).unwrap();
+    assert_eq!(removed, ["5", "0", "3"]);
+    assert_eq!(slab.len(), 4);
+    assert!(!slab.contains(keys[0]));
+    assert_eq!(slab.remove_many(&[]), Ok(vec![]));
+
+    // The removed keys are reused lowest first, then the older vacant key
+    let reused: Vec<_> = (0..5).map(|_| slab.insert(String::new())).collect();
+    assert_eq!(reused, [keys[0], keys[3], keys[5], keys[6], 8]);
+}
+
+#[test]
+fn insert_contiguous() {
+    let mut slab = Slab::new();
+    for i in 0..8 {
+        slab.insert(i);
+    }
+    for &key in &[6, 1, 4, 3, 7] {
+        slab.remove(key);
+    }
+    assert_eq!(slab.vacant_key(), 7);
+
+    // The lowest long enough run is used
+    assert_eq!(slab.insert_contiguous(vec![30, 40]), 3..5);
+    assert_eq!(slab[3], 30);
+    assert_eq!(slab[4], 40);
+    assert_eq!(slab.len(), 5);
+
+    // The trailing vacant entries are extended
+    let run = slab.reserve_contiguous(3);
+    assert_eq!(run.keys(), 6..9);
+    assert_eq!(run.insert(60..63), 6..9);
+    assert_eq!(slab.len(), 8);
+
+    // The remaining vacant entry is still linked, followed by new keys
+    assert_eq!(slab.insert_contiguous(iter::empty()).len(), 0);
+    assert_eq!(slab.insert(10), 1);
+    assert_eq!(slab.insert(90), 9);
+    assert_eq!(
+        slab.iter().map(|(_, &v)| v).collect::<Vec<_>>(),
+        [0, 10, 2, 30, 40, 5, 60, 61, 62, 90]
+    );
+}
+
+#[test]
+fn reserve_contiguous_keys_stay_vacant() {
+    let mut slab = Slab::new();
+    for i in 0..4 {
+        slab.insert(i);
+    }
+    slab.remove(1);
+    slab.remove(2);
+
+    // Dropping the run leaves the vacant list as it was
+    assert_eq!(slab.reserve_contiguous(2).keys(), 1..3);
+    assert_eq!(slab.vacant_key(), 2);
+
+    let run = slab.reserve_contiguous(3);
+    let keys = run.keys();
+    assert_eq!(keys, 4..7);
+    run.insert(keys.map(|key| key * 10));
+    assert_eq!(slab[5], 50);
+    assert_eq!(slab.insert(10), 2);
+    assert_eq!(slab.insert(20), 1);
+    assert_eq!(slab.insert(30), 7);
+}
+
+#[test]
+#[should_panic(expected = "wrong number of values")]
+fn reserve_contiguous_wrong_number_of_values() {
+    let mut slab = Slab::new();
+    slab.reserve_contiguous(2).insert(vec![1]);
+}
+
+#[test]
+#[should_panic(expected = "capacity overflow")]
+fn reserve_contiguous_overflow() {
+    let mut slab = Slab::new();
+    slab.insert(0);
+    slab.reserve_contiguous(usize::MAX);
+}
+
+#[test]
+fn insert_contiguous_panic_leaves_slab_unchanged() {
+    let mut slab = Slab::new();
+    slab.insert(0);
+    slab.insert(1);
+    slab.remove(0);
+
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        slab.insert_contiguous((0..3).map(|i| if i == 2 { panic!("test") } else { i }))
+    }));
+    match result {
+        Err(ref payload) if payload.downcast_ref() == Some(&"test") => {}
+        Err(bug) => resume_unwind(bug),
+        Ok(_) => unreachable!(),
+    }
+
+    assert_eq!(slab.len(), 1);
+    assert_eq!(slab.insert_contiguous(vec![5]), 0..1);
+    assert_eq!(slab.insert(6), 2);
+}
+
+#[test]
+fn map_keeps_layout() {
+    let mut slab = Slab::with_capacity(16);
+    for i in 0..6 {
+        slab.insert(i);
+    }
+    slab.remove(4);
+    slab.remove(1);
+    let mut expected = slab.clone();
+
+    let mut strings = slab.map(|key, value| {
+        assert_eq!(key, value);
+        value.to_string()
+    });
+    assert_eq!(strings.len(), 4);
+    assert_eq!(strings.capacity(), 16);
+    assert_eq!(strings[5], "5");
+    for _ in 0..4 {
+        assert_eq!(strings.insert(String::new()), expected.insert(0));
+    }
+}
+
+#[test]
+fn try_map_stops_at_first_error() {
+    let mut slab = Slab::new();
+    for i in 0..5 {
+        slab.insert(i);
+    }
+    slab.remove(0);
+
+    let mut seen = vec![];
+    let result = slab.clone().try_map(|key, value| {
+        seen.push(key);
+        if value == 2 {
+            Err(key)
+        } else {
+            Ok(value * 2)
+        }
+    });
+    assert_eq!(result.unwrap_err(), 2);
+    assert_eq!(seen, [1, 2]);
+
+    let mut doubled = slab.try_map(|_, value| Ok::<_, ()>(value * 2)).unwrap();
+    assert_eq!(doubled[4], 8);
+    assert_eq!(doubled.insert(0), 0);
+}
+
+#[test]
+fn filter_map_vacates_like_retain() {
+    let mut slab = Slab::with_capacity(8);
+    for i in 0..6 {
+        slab.insert(i);
+    }
+    slab.remove(2);
+
+    let mut retained = slab.clone();
+    retained.retain(|_, value| *value % 3 != 0);
+    let mut filtered = slab.filter_map(|_, value| if value % 3 != 0 { Some(value) } else { None });
+
+    assert_eq!(filtered.len(), retained.len());
+    assert_eq!(
+        filtered.iter().collect::<Vec<_>>(),
+        retained.iter().collect::<Vec<_>>()
+    );
+    for _ in 0..5 {
+        assert_eq!(filtered.insert(0), retained.insert(0));
+    }
+}
@@ -0,0 +1,259 @@
+#![cfg(feature = "derive")]
+#![warn(rust_2018_idioms)]
+
+use slab::list::{AsSliceListStorage, CapacityListStorage, List, ListStorage, MutRefListStorage};
+use slab::SlabSoA;
+
+use std::rc::Rc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+#[derive(SlabSoA, Debug, PartialEq)]
+struct Entity {
+    name: String,
+    health: u32,
+    alive: bool,
+}
+
+fn entity(name: &str, health: u32) -> Entity {
+    Entity {
+        name: name.to_string(),
+        health,
+        alive: true,
+    }
+}
+
+#[test]
+fn insert_get_remove() {
+    let mut slab = EntitySlab::with_capacity(2);
+    assert!(slab.is_empty());
+
+    let a = slab.insert(entity("a", 10));
+    let b = slab.insert(entity("b", 20));
+    let c = slab.insert(entity("c", 30));
+    assert_eq!((a, b, c), (0, 1, 2));
+    assert_eq!(slab.len(), 3);
+
+    let item = slab.get(b).unwrap();
+    assert_eq!((item.name.as_str(), *item.health), ("b", 20));
+
+    *slab.get_mut(c).unwrap().alive = false;
+    assert_eq!(
+        slab.remove(c),
+        Entity {
+            name: "c".to_string(),
+            health: 30,
+            alive: false,
+        }
+    );
+    assert!(slab.get(c).is_none());
+    assert!(slab.get_mut(c).is_none());
+    assert_eq!(slab.try_remove(c), None);
+
+    // Keys are reused like they are by `Slab`
+    assert_eq!(slab.remove(a), entity("a", 10));
+    assert_eq!(slab.insert(entity("d", 40)), a);
+    assert!(slab.contains(a) && slab.contains(b));
+}
+
+#[test]
+fn column_iterators_skip_vacant_keys() {
+    let mut slab = EntitySlab::new();
+    for i in 0..6 {
+        slab.insert(entity(&i.to_string(), i));
+    }
+    slab.remove(0);
+    slab.remove(3);
+    slab.remove(5);
+
+    for (_, health) in slab.iter_health_mut() {
+        *health *= 10;
+    }
+    let columns = slab.columns_mut();
+    for ((_, health), (_, alive)) in columns.health.zip(columns.alive) {
+        *alive = *health > 15;
+    }
+
+    let health: Vec<_> = slab.iter_health().collect();
+    assert_eq!(health, [(1, &10), (2, &20), (4, &40)]);
+    let alive: Vec<_> = slab.iter_alive().map(|(_, &alive)| alive).collect();
+    assert_eq!(alive, [false, true, true]);
+    let names: Vec<_> = slab
+        .iter()
+        .map(|(key, item)| (key, item.name.clone()))
+        .collect();
+    assert_eq!(names, [(1, "1".into()), (2, "2".into()), (4, "4".into())]);
+}
+
+#[derive(SlabSoA)]
+struct Tracked {
+    a: Rc<()>,
+    b: Rc<()>,
+}
+
+#[test]
+fn drops_occupied_values_only() {
+    let rc = Rc::new(());
+    let tracked = || Tracked {
+        a: rc.clone(),
+        b: rc.clone(),
+    };
+
+    let mut slab = TrackedSlab::new();
+    for _ in 0..4 {
+        slab.insert(tracked());
+    }
+    drop(slab.remove(1));
+    assert_eq!(Rc::strong_count(&rc), 7);
+
+    slab.clear();
+    assert_eq!(Rc::strong_count(&rc), 1);
+    assert!(slab.is_empty());
+
+    slab.insert(tracked());
+    slab.insert(tracked());
+    slab.remove(0);
+    drop(slab);
+    assert_eq!(Rc::strong_count(&rc), 1);
+}
+
+#[test]
+fn clear_keeps_capacity() {
+    let mut slab = EntitySlab::with_capacity(8);
+    for i in 0..8 {
+        slab.insert(entity("e", i));
+    }
+    let capacity = slab.capacity();
+
+    slab.clear();
+    assert!(slab.is_empty());
+    assert_eq!(slab.capacity(), capacity);
+    assert_eq!(slab.insert(entity("f", 1)), 0);
+    assert_eq!(
+        slab.iter_health().map(|(_, &health)| health).sum::<u32>(),
+        1
+    );
+}
+
+#[test]
+fn clear_panicking_destructor_leaks_rest() {
+    struct PanicOnDrop(bool);
+
+    impl Drop for PanicOnDrop {
+        fn drop(&mut self) {
+            if self.0 {
+                panic!("drop");
+            }
+        }
+    }
+
+    #[derive(SlabSoA)]
+    struct Item {
+        value: PanicOnDrop,
+        rc: Rc<()>,
+    }
+
+    let rc = Rc::new(());
+    let mut slab = ItemSlab::new();
+    for i in 0..3 {
+        slab.insert(Item {
+            value: PanicOnDrop(i == 1),
+            rc: rc.clone(),
+        });
+    }
+
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| slab.clear()));
+    assert!(result.is_err());
+    assert!(slab.is_empty());
+    // The value after the panicking one is leaked
+    assert_eq!(Rc::strong_count(&rc), 2);
+    drop(slab);
+    assert_eq!(Rc::strong_count(&rc), 2);
+}
+
+mod reexport {
+    pub use slab;
+}
+
+#[test]
+fn crate_path_override() {
+    #[derive(SlabSoA)]
+    #[slab(crate = "crate::reexport::slab")]
+    struct Point {
+        x: i32,
+        y: i32,
+    }
+
+    let mut slab = PointSlab::new();
+    let key = slab.insert(Point { x: 1, y: 2 });
+    let point = slab.remove(key);
+    assert_eq!((point.x, point.y), (1, 2));
+}
+
+// A `Vec` storage that counts the columns it creates
+enum CountingStorage {}
+
+static COUNTING_COLUMNS: AtomicUsize = AtomicUsize::new(0);
+
+impl ListStorage for CountingStorage {
+    type List<T> = Vec<T>;
+}
+
+impl CapacityListStorage for CountingStorage {
+    fn with_capacity<T>(capacity: usize) -> Vec<T> {
+        COUNTING_COLUMNS.fetch_add(1, Ordering::SeqCst);
+        Vec::with_capacity(capacity)
+    }
+    fn capacity<T>(list: &Vec<T>) -> usize {
+        list.capacity()
+    }
+    fn reserve<T>(list: &mut Vec<T>, additional: usize) {
+        list.reserve(additional)
+    }
+}
+
+impl AsSliceListStorage for CountingStorage {
+    fn as_slice<T>(list: &Vec<T>) -> &[T] {
+        list
+    }
+    fn as_mut_slice<T>(list: &mut Vec<T>) -> &mut [T] {
+        list
+    }
+}
+
+impl MutRefListStorage for CountingStorage {
+    fn into_mut_ref<T>(item_mut: <Vec<T> as List>::ItemMut<'_>) -> &mut T {
+        item_mut
+    }
+}
+
+#[test]
+fn column_storage_per_field() {
+    #[derive(SlabSoA)]
+    struct Sample {
+        #[slab(storage = "CountingStorage")]
+        id: u32,
+        name: String,
+    }
+
+    let mut slab = SampleSlab::with_capacity(2);
+    // Only the `id` column uses the custom storage
+    assert_eq!(COUNTING_COLUMNS.load(Ordering::SeqCst), 1);
+
+    let a = slab.insert(Sample {
+        id: 1,
+        name: "a".to_string(),
+    });
+    slab.insert(Sample {
+        id: 2,
+        name: "b".to_string(),
+    });
+    for (_, id) in slab.iter_id_mut() {
+        *id *= 10;
+    }
+    *slab.get_mut(a).unwrap().id += 1;
+    assert_eq!(
+        slab.iter_id().map(|(_, &id)| id).collect::<Vec<_>>(),
+        [11, 20]
+    );
+    assert_eq!(slab.remove(a).name, "a");
+}
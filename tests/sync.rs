@@ -0,0 +1,173 @@
+#![warn(rust_2018_idioms)]
+
+use slab::sync::Slab;
+
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+
+#[test]
+fn insert_get_remove_one() {
+    let slab = Slab::with_capacity(1);
+    assert!(slab.is_empty());
+
+    let key = slab.insert(10).unwrap();
+    assert_eq!(*slab.get(key).unwrap(), 10);
+    assert_eq!(slab.len(), 1);
+    assert!(slab.contains(key));
+
+    assert!(slab.remove(key));
+    assert!(!slab.remove(key));
+    assert!(slab.get(key).is_none());
+    assert!(slab.is_empty());
+}
+
+#[test]
+fn insert_full() {
+    let slab = Slab::with_capacity(2);
+    let a = slab.insert('a').unwrap();
+    slab.insert('b').unwrap();
+    assert_eq!(slab.insert('c'), Err('c'));
+
+    slab.remove(a);
+    assert_eq!(slab.insert('c'), Ok(a));
+}
+
+#[test]
+fn take_and_get_mut() {
+    let mut slab = Slab::with_capacity(2);
+    let key = slab.insert(1).unwrap();
+    *slab.get_mut(key).unwrap() += 1;
+    assert_eq!(slab.take(key), Some(2));
+    assert_eq!(slab.take(key), None);
+    assert!(slab.get_mut(key).is_none());
+    assert_eq!(slab.insert(3), Ok(key));
+}
+
+#[derive(Debug)]
+struct Tracked(Arc<AtomicUsize>);
+
+impl Drop for Tracked {
+    fn drop(&mut self) {
+        self.0.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[test]
+fn remove_with_outstanding_refs() {
+    let drops = Arc::new(AtomicUsize::new(0));
+    let slab = Slab::with_capacity(1);
+    let key = slab.insert(Tracked(drops.clone())).unwrap();
+
+    let first = slab.get(key).unwrap();
+    let second = slab.get(key).unwrap();
+    assert!(slab.remove(key));
+    assert!(slab.get(key).is_none());
+    assert_eq!(slab.len(), 0);
+    // The key is not released yet
+    assert!(slab.insert(Tracked(Arc::default())).is_err());
+
+    drop(first);
+    assert_eq!(drops.load(Ordering::SeqCst), 0);
+    drop(second);
+    assert_eq!(drops.load(Ordering::SeqCst), 1);
+    assert_eq!(slab.len(), 0);
+
+    assert_eq!(slab.insert(Tracked(drops.clone())).ok(), Some(key));
+    drop(slab);
+    assert_eq!(drops.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn concurrent_insert_remove() {
+    const THREADS: usize = 8;
+    const ITERATIONS: usize = if cfg!(miri) { 20 } else { 10_000 };
+
+    let slab = Slab::with_capacity(THREADS * 2);
+    thread::scope(|scope| {
+        for thread in 0..THREADS {
+            let slab = &slab;
+            scope.spawn(move || {
+                for i in 0..ITERATIONS {
+                    let a = slab.insert((thread, i)).unwrap();
+                    let b = slab.insert((thread, i + 1)).unwrap();
+                    assert_ne!(a, b);
+                    assert_eq!(*slab.get(a).unwrap(), (thread, i));
+                    assert_eq!(*slab.get(b).unwrap(), (thread, i + 1));
+                    assert!(slab.remove(a));
+                    assert!(slab.remove(b));
+                }
+            });
+        }
+    });
+
+    assert!(slab.is_empty());
+    for key in 0..slab.capacity() {
+        assert!(slab.get(key).is_none());
+    }
+}
+
+#[test]
+fn concurrent_keys_are_exclusive() {
+    // More threads than slots keep the same few slots cycling through the
+    // head of the vacant stack, which is where ABA would hand out a slot twice
+    const THREADS: usize = 8;
+    const ITERATIONS: usize = if cfg!(miri) { 20 } else { 20_000 };
+
+    let slab = Slab::with_capacity(2);
+    let owned: Vec<AtomicBool> = (0..slab.capacity())
+        .map(|_| AtomicBool::new(false))
+        .collect();
+    thread::scope(|scope| {
+        for thread in 0..THREADS {
+            let (slab, owned) = (&slab, &owned);
+            scope.spawn(move || {
+                for i in 0..ITERATIONS {
+                    let key = match slab.insert((thread, i)) {
+                        Ok(key) => key,
+                        Err(_) => continue,
+                    };
+                    assert!(!owned[key].swap(true, Ordering::SeqCst));
+                    assert_eq!(*slab.get(key).unwrap(), (thread, i));
+                    owned[key].store(false, Ordering::SeqCst);
+                    assert!(slab.remove(key));
+                }
+            });
+        }
+    });
+
+    assert!(slab.is_empty());
+}
+
+#[test]
+fn concurrent_get_remove() {
+    let drops = Arc::new(AtomicUsize::new(0));
+    let slab = Slab::with_capacity(64);
+    let keys: Vec<_> = (0..64)
+        .map(|_| slab.insert(Tracked(drops.clone())).unwrap())
+        .collect();
+
+    thread::scope(|scope| {
+        for _ in 0..4 {
+            let (slab, keys) = (&slab, &keys);
+            scope.spawn(move || {
+                for &key in keys {
+                    if let Some(value) = slab.get(key) {
+                        assert_eq!(value.key(), key);
+                    }
+                }
+            });
+        }
+        for _ in 0..2 {
+            let (slab, keys) = (&slab, &keys);
+            scope.spawn(move || {
+                for &key in keys {
+                    slab.remove(key);
+                }
+            });
+        }
+    });
+
+    assert!(slab.is_empty());
+    assert_eq!(drops.load(Ordering::SeqCst), 64);
+}
@@ -0,0 +1,112 @@
+#![cfg(feature = "timer")]
+#![warn(rust_2018_idioms)]
+
+use slab::timer::Wheel;
+
+#[test]
+fn fires_at_deadline() {
+    let mut wheel = Wheel::with_slots(8);
+    let a = wheel.insert(3, 'a');
+    let b = wheel.insert(3, 'b');
+    wheel.insert(5, 'c');
+    assert_eq!(wheel.deadline(a), Some(3));
+
+    assert_eq!(wheel.poll(2).count(), 0);
+    let mut fired: Vec<_> = wheel.poll(3).collect();
+    fired.sort();
+    assert_eq!(fired, ['a', 'b']);
+    assert!(!wheel.contains(a) && !wheel.contains(b));
+
+    // Polling the same tick again finds timers inserted for it since
+    wheel.insert(3, 'd');
+    assert_eq!(wheel.poll(3).collect::<Vec<_>>(), ['d']);
+    assert_eq!(wheel.poll(10).collect::<Vec<_>>(), ['c']);
+    assert!(wheel.is_empty());
+}
+
+#[test]
+fn past_deadline_fires_on_next_poll() {
+    let mut wheel = Wheel::with_slots(8);
+    assert_eq!(wheel.poll(100).count(), 0);
+
+    wheel.insert(50, "late");
+    assert_eq!(wheel.poll(100).collect::<Vec<_>>(), ["late"]);
+}
+
+#[test]
+fn timers_beyond_one_revolution() {
+    let mut wheel = Wheel::with_slots(4);
+    wheel.insert(1, 1);
+    wheel.insert(5, 5);
+    wheel.insert(9, 9);
+
+    assert_eq!(wheel.poll(1).collect::<Vec<_>>(), [1]);
+    assert_eq!(wheel.poll(8).collect::<Vec<_>>(), [5]);
+    // A long pause visits every slot once
+    assert_eq!(wheel.poll(1_000).collect::<Vec<_>>(), [9]);
+}
+
+#[test]
+fn cancel_keeps_slot_list() {
+    let mut wheel = Wheel::with_slots(4);
+    let keys: Vec<_> = (0..5).map(|i| wheel.insert(2, i)).collect();
+
+    assert_eq!(wheel.cancel(keys[2]), Some(2));
+    assert_eq!(wheel.cancel(keys[2]), None);
+    assert_eq!(wheel.cancel(keys[4]), Some(4));
+    assert_eq!(wheel.cancel(keys[0]), Some(0));
+
+    let mut fired: Vec<_> = wheel.poll(2).collect();
+    fired.sort();
+    assert_eq!(fired, [1, 3]);
+}
+
+#[test]
+fn dropped_poll_resumes() {
+    let mut wheel = Wheel::with_slots(16);
+    for deadline in 1..=10 {
+        wheel.insert(deadline, deadline);
+    }
+
+    let first: Vec<_> = wheel.poll(10).take(3).collect();
+    assert_eq!(first, [1, 2, 3]);
+    assert_eq!(wheel.len(), 7);
+    assert_eq!(wheel.poll(10).collect::<Vec<_>>(), [4, 5, 6, 7, 8, 9, 10]);
+}
+
+#[test]
+fn mock_clock_against_model() {
+    let mut state = 0x9e37_79b9_7f4a_7c15u64;
+    let mut next = move |bound: u64| {
+        state = state.wrapping_mul(6364136223846793005).wrapping_add(1);
+        (state >> 33) % bound
+    };
+
+    let mut wheel = Wheel::with_slots(16);
+    let mut pending = Vec::new();
+    let mut now = 0;
+
+    for _ in 0..500 {
+        for _ in 0..next(4) {
+            let deadline = now + next(100);
+            pending.push((wheel.insert(deadline, deadline), deadline));
+        }
+        if next(5) == 0 && !pending.is_empty() {
+            let (key, deadline) = pending.swap_remove(next(pending.len() as u64) as usize);
+            assert_eq!(wheel.cancel(key), Some(deadline));
+        }
+
+        now += next(20);
+        let mut fired: Vec<_> = wheel.poll(now).collect();
+        fired.sort_unstable();
+        let mut expected: Vec<_> = pending
+            .iter()
+            .map(|&(_, deadline)| deadline)
+            .filter(|&deadline| deadline <= now)
+            .collect();
+        expected.sort_unstable();
+        assert_eq!(fired, expected);
+        pending.retain(|&(_, deadline)| deadline > now);
+        assert_eq!(wheel.len(), pending.len());
+    }
+}